@@ -0,0 +1,60 @@
+//! 对比默认的 `LinkedList` 与开启节点回收后, 频繁进出队列的耗时
+//! `cargo bench --bench pool`
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use too_many_linked_list::unsafe_deque::LinkedList;
+
+const ROUNDS: usize = 20;
+const OPS: usize = 100_000;
+const QUEUE_LEN: usize = 64;
+
+/// 队列保持 `QUEUE_LEN` 个元素, 每次从队尾进一个, 从队首出一个
+fn churn(mut list: LinkedList<u64>) -> Duration {
+    for i in 0..QUEUE_LEN as u64 {
+        list.push_back(i);
+    }
+    let start = Instant::now();
+    for i in 0..OPS as u64 {
+        list.push_back(black_box(i));
+        black_box(list.pop_front());
+    }
+    start.elapsed()
+}
+
+/// 整批进整批出
+fn burst(mut list: LinkedList<u64>) -> Duration {
+    let start = Instant::now();
+    for _ in 0..OPS / QUEUE_LEN {
+        for i in 0..QUEUE_LEN as u64 {
+            list.push_back(black_box(i));
+        }
+        while let Some(x) = list.pop_front() {
+            black_box(x);
+        }
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, mut run: impl FnMut() -> Duration) {
+    let mut best = Duration::MAX;
+    let mut total = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let elapsed = run();
+        best = best.min(elapsed);
+        total += elapsed;
+    }
+    println!(
+        "{:<24} best {:>8.2} ns/op  mean {:>8.2} ns/op",
+        name,
+        best.as_nanos() as f64 / OPS as f64,
+        total.as_nanos() as f64 / (OPS * ROUNDS) as f64,
+    );
+}
+
+fn main() {
+    report("churn/alloc", || churn(LinkedList::new()));
+    report("churn/pool", || churn(LinkedList::with_capacity(QUEUE_LEN + 1)));
+    report("burst/alloc", || burst(LinkedList::new()));
+    report("burst/pool", || burst(LinkedList::with_capacity(QUEUE_LEN)));
+}
@@ -0,0 +1,52 @@
+//! 对比 `LinkedStack` 与 `TailedStack` 在队尾操作上的耗时
+//! `cargo bench --bench stack_tail`
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use too_many_linked_list::linked_stack::{LinkedStack, TailedStack};
+
+const ROUNDS: usize = 5;
+
+/// 从队尾进 `n` 个, 边看队尾边从队尾全部出去
+fn linked(n: usize) -> Duration {
+    let mut stack = LinkedStack::new();
+    let start = Instant::now();
+    for i in 0..n {
+        stack.push_back(black_box(i));
+    }
+    while let Some(&x) = stack.peek_back() {
+        black_box(x);
+        black_box(stack.pop_back());
+    }
+    start.elapsed()
+}
+
+fn tailed(n: usize) -> Duration {
+    let mut stack = TailedStack::new();
+    let start = Instant::now();
+    for i in 0..n {
+        stack.push_back(black_box(i));
+    }
+    while let Some(&x) = stack.peek_back() {
+        black_box(x);
+        black_box(stack.pop_back());
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, n: usize, run: impl Fn(usize) -> Duration) {
+    let best = (0..ROUNDS).map(|_| run(n)).min().unwrap();
+    println!(
+        "{:<16} n = {:<7} best {:>10.2} ns/op",
+        name,
+        n,
+        best.as_nanos() as f64 / (3 * n) as f64,
+    );
+}
+
+fn main() {
+    for n in [100, 1_000, 10_000] {
+        report("linked_stack", n, linked);
+        report("tailed_stack", n, tailed);
+    }
+}
@@ -1,6 +1,8 @@
 //! [2.1. Layout](http://rust-unofficial.github.io/too-many-lists/first-layout.html)
 //! 不好的数据结构
 
+use alloc::boxed::Box;
+
 #[cfg(doctest)]
 /// 自包含结构
 /// ```compile_fail
@@ -39,6 +41,7 @@ pub enum List3<T> {
     More(Box<Node3<T>>),
 }
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Node3<T> {
     elem: T,
     next: List3<T>,
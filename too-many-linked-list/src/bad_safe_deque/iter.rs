@@ -1,11 +1,12 @@
 use super::*;
 
+#[allow(dead_code)]
 pub struct Iter<T>(Link<T>, Link<T>);
 
 impl<T> Iterator for Iter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.as_mut().map(|node| {
+        self.0.as_mut().map(|_node| {
             todo!()
         })
     }
@@ -21,6 +21,8 @@ mod iter_mut;
 
 #[cfg(test)]
 mod test;
+#[cfg(feature = "serde")]
+mod serde;
 
 impl<T> List<T> {
     pub fn new() -> Self {
@@ -123,6 +125,12 @@ impl<T> List<T> {
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         while self.pop_front().is_some() {}
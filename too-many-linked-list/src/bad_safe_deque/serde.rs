@@ -0,0 +1,44 @@
+//! 序列化为从队首到队尾的序列
+//! `iter` 还没有实现, 序列化时逐个节点加读锁
+use super::*;
+use core::fmt;
+use core::marker::PhantomData;
+use ::serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use ::serde::ser::{Serialize, SerializeSeq, Serializer};
+
+impl<T: Serialize> Serialize for List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut link = self.head.clone();
+        while let Some(node) = link {
+            let node = node.read().unwrap();
+            seq.serialize_element(&node.elem)?;
+            link = node.next.clone();
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ListVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+            type Value = List<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut list = List::new();
+                while let Some(elem) = seq.next_element()? {
+                    list.push_back(elem);
+                }
+                Ok(list)
+            }
+        }
+
+        deserializer.deserialize_seq(ListVisitor(PhantomData))
+    }
+}
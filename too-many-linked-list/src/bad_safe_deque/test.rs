@@ -76,9 +76,9 @@ fn peek() {
 
     list.push_front(1); list.push_front(2); list.push_front(3);
 
-    assert_eq!(&*list.peek_front().unwrap(), &3);
+    assert_eq!(list.peek_front().unwrap(), &3);
     assert_eq!(&mut *list.peek_mut_front().unwrap(), &mut 3);
-    assert_eq!(&*list.peek_back().unwrap(), &1);
+    assert_eq!(list.peek_back().unwrap(), &1);
     assert_eq!(&mut *list.peek_mut_back().unwrap(), &mut 1);
 }
 
@@ -93,4 +93,23 @@ fn into_iter() {
     assert_eq!(iter.next(), Some(2));
     assert_eq!(iter.next_back(), None);
     assert_eq!(iter.next(), None);
-}
\ No newline at end of file
+}
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use serde_test::{assert_ser_tokens, Token};
+    let mut list = List::new();
+    list.push_back(1);
+    list.push_back(2);
+    list.push_front(0);
+    assert_ser_tokens(&list, &[
+        Token::Seq { len: None },
+        Token::I32(0),
+        Token::I32(1),
+        Token::I32(2),
+        Token::SeqEnd,
+    ]);
+
+    let mut list: List<i32> = serde_json::from_str("[]").unwrap();
+    assert_eq!(list.pop_front(), None);
+}
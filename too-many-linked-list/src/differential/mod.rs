@@ -0,0 +1,116 @@
+//! 随机操作差分测试
+//!
+//! 1. 用固定种子的伪随机数生成操作序列, 不依赖外部服务, 失败可以按种子复现
+//! 2. 每个 `Target` 同时持有被测链表和 `VecDeque` 模型, 每一步都比较两者
+//! 3. 出错时不断删掉操作序列中的片段, 直到再删任何一个操作都不出错, 得到最短的复现
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+mod targets;
+mod test;
+
+/// xorshift64*, 质量足够生成操作序列
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // 状态不能为 0
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// `[0, n)`
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// 元素取值范围小一些, 容易出现相等的元素
+    pub(crate) fn value(&mut self) -> i32 {
+        self.below(100) as i32
+    }
+}
+
+/// 被测链表和模型的组合
+pub(crate) trait Target {
+    type Op: Debug + Clone;
+
+    fn new() -> Self;
+
+    fn gen_op(rng: &mut Rng) -> Self::Op;
+
+    /// 同时作用在被测链表和模型上, 结果或内容不一致时 panic
+    fn apply(&mut self, op: &Self::Op);
+}
+
+/// 从头执行一遍, 返回 panic 的信息
+fn run<T: Target>(ops: &[T::Op]) -> Result<(), String> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut target = T::new();
+        for op in ops {
+            target.apply(op);
+        }
+    }))
+    .map_err(|payload| {
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_default()
+    })
+}
+
+/// 删掉片段后仍然出错就保留删除, 片段从一半开始逐步减到单个操作
+fn shrink<T: Target>(mut ops: Vec<T::Op>) -> Vec<T::Op> {
+    let mut chunk = ops.len() / 2;
+    while chunk > 0 {
+        let mut removed = false;
+        let mut i = 0;
+        while i < ops.len() {
+            let mut candidate = ops.clone();
+            candidate.drain(i..(i + chunk).min(ops.len()));
+            if run::<T>(&candidate).is_err() {
+                ops = candidate;
+                removed = true;
+            } else {
+                i += chunk;
+            }
+        }
+        if !removed {
+            chunk /= 2;
+        }
+    }
+    ops
+}
+
+/// 对每个种子生成 `steps` 个操作并执行, 出错时缩减后 panic, 打印种子和最短的操作序列
+pub(crate) fn check<T: Target>(seeds: std::ops::Range<u64>, steps: usize) {
+    for seed in seeds {
+        let mut rng = Rng::new(seed);
+        let ops: Vec<_> = (0..steps).map(|_| T::gen_op(&mut rng)).collect();
+        if run::<T>(&ops).is_ok() {
+            continue;
+        }
+        // 缩减时会 panic 很多次, 暂时不打印
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let ops = shrink::<T>(ops);
+        panic::set_hook(hook);
+        let message = run::<T>(&ops).unwrap_err();
+        panic!(
+            "{} failed with seed {}, shrunk to {} ops: {:?}\n{}",
+            std::any::type_name::<T>(),
+            seed,
+            ops.len(),
+            ops,
+            message
+        );
+    }
+}
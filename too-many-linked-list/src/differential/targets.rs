@@ -0,0 +1,695 @@
+//! 各个链表与模型的对应关系
+use super::{Rng, Target};
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::bad_safe_deque;
+use crate::linked_stack::LinkedStack;
+use crate::shared_linked_list::{PersistentDeque, PersistentQueue};
+use crate::silly_lists::double_single;
+use crate::unsafe_deque::LinkedList;
+use crate::unsafe_queue;
+
+/// 模型是 `Vec`, 栈顶在末尾
+pub(crate) struct Stack {
+    list: LinkedStack<i32>,
+    model: Vec<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum StackOp {
+    Push(i32),
+    Pop,
+    PushBack(i32),
+    PopBack,
+    SetTop(i32),
+}
+
+impl Target for Stack {
+    type Op = StackOp;
+
+    fn new() -> Self {
+        Self { list: LinkedStack::new(), model: Vec::new() }
+    }
+
+    fn gen_op(rng: &mut Rng) -> StackOp {
+        match rng.below(5) {
+            0 => StackOp::Push(rng.value()),
+            1 => StackOp::Pop,
+            2 => StackOp::PushBack(rng.value()),
+            3 => StackOp::PopBack,
+            _ => StackOp::SetTop(rng.value()),
+        }
+    }
+
+    fn apply(&mut self, op: &StackOp) {
+        match *op {
+            StackOp::Push(v) => {
+                self.list.push(v);
+                self.model.push(v);
+            }
+            StackOp::Pop => assert_eq!(self.list.pop(), self.model.pop(), "pop"),
+            StackOp::PushBack(v) => {
+                self.list.push_back(v);
+                self.model.insert(0, v);
+            }
+            StackOp::PopBack => {
+                let expected = if self.model.is_empty() { None } else { Some(self.model.remove(0)) };
+                assert_eq!(self.list.pop_back(), expected, "pop_back");
+            }
+            StackOp::SetTop(v) => {
+                if let Some(top) = self.list.peek_mut() {
+                    *top = v;
+                }
+                if let Some(top) = self.model.last_mut() {
+                    *top = v;
+                }
+            }
+        }
+        assert!(self.list.iter().eq(self.model.iter().rev()), "contents differ after {:?}", op);
+        assert_eq!(self.list.peek_back(), self.model.first());
+    }
+}
+
+pub(crate) struct Queue {
+    list: unsafe_queue::List<i32>,
+    model: VecDeque<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum QueueOp {
+    PushFront(i32),
+    PushBack(i32),
+    PopFront,
+    Reverse,
+    AddAll(i32),
+}
+
+impl Target for Queue {
+    type Op = QueueOp;
+
+    fn new() -> Self {
+        Self { list: unsafe_queue::List::new(), model: VecDeque::new() }
+    }
+
+    fn gen_op(rng: &mut Rng) -> QueueOp {
+        match rng.below(8) {
+            0 | 1 => QueueOp::PushFront(rng.value()),
+            2 | 3 => QueueOp::PushBack(rng.value()),
+            4 | 5 => QueueOp::PopFront,
+            6 => QueueOp::Reverse,
+            _ => QueueOp::AddAll(rng.value()),
+        }
+    }
+
+    fn apply(&mut self, op: &QueueOp) {
+        match *op {
+            QueueOp::PushFront(v) => {
+                self.list.push_front(v);
+                self.model.push_front(v);
+            }
+            QueueOp::PushBack(v) => {
+                self.list.push_back(v);
+                self.model.push_back(v);
+            }
+            QueueOp::PopFront => assert_eq!(self.list.pop_front(), self.model.pop_front(), "pop_front"),
+            QueueOp::Reverse => {
+                unsafe_queue::reverse(&mut self.list);
+                self.model.make_contiguous().reverse();
+            }
+            QueueOp::AddAll(v) => {
+                self.list.iter_mut().for_each(|x| *x += v);
+                self.model.iter_mut().for_each(|x| *x += v);
+            }
+        }
+        self.list.check_invariants();
+        assert!(self.list.iter().eq(self.model.iter()), "contents differ after {:?}", op);
+        assert_eq!(self.list.peek(), self.model.front());
+    }
+}
+
+/// 两个链表 `list` `other`, 切下来的放进 `other`, 接上去的从 `other` 拿;
+/// 游标位置在操作之间保存为 index, 每次重新建游标走过去
+pub(crate) struct Deque {
+    list: LinkedList<i32>,
+    other: LinkedList<i32>,
+    // 游标位置, `None` 为幽灵元素
+    cursor: Option<usize>,
+    model: VecDeque<i32>,
+    model_other: VecDeque<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum DequeOp {
+    PushFront(i32),
+    PushBack(i32),
+    PopFront,
+    PopBack,
+    MoveNext,
+    MovePrev,
+    InsertBefore(i32),
+    InsertAfter(i32),
+    RemoveCurrent,
+    ReplaceCurrent(i32),
+    RemoveCurrentAsList,
+    SplitBefore,
+    SplitAfter,
+    SpliceBefore,
+    SpliceAfter,
+    Append,
+    SplitOff(usize),
+    Retain(i32),
+    Sort,
+    Reverse,
+    RotateLeft(usize),
+    Swap(usize, usize),
+    Clear,
+}
+
+fn model_current(model: &VecDeque<i32>, cursor: Option<usize>) -> Option<i32> {
+    cursor.map(|i| model[i])
+}
+
+impl Target for Deque {
+    type Op = DequeOp;
+
+    fn new() -> Self {
+        Self {
+            list: LinkedList::new(),
+            other: LinkedList::new(),
+            cursor: None,
+            model: VecDeque::new(),
+            model_other: VecDeque::new(),
+        }
+    }
+
+    fn gen_op(rng: &mut Rng) -> DequeOp {
+        match rng.below(27) {
+            0 | 1 => DequeOp::PushFront(rng.value()),
+            2 | 3 => DequeOp::PushBack(rng.value()),
+            4 => DequeOp::PopFront,
+            5 => DequeOp::PopBack,
+            6 | 7 => DequeOp::MoveNext,
+            8 | 9 => DequeOp::MovePrev,
+            10 => DequeOp::InsertBefore(rng.value()),
+            11 => DequeOp::InsertAfter(rng.value()),
+            12 => DequeOp::RemoveCurrent,
+            13 => DequeOp::ReplaceCurrent(rng.value()),
+            14 => DequeOp::RemoveCurrentAsList,
+            15 => DequeOp::SplitBefore,
+            16 => DequeOp::SplitAfter,
+            17 => DequeOp::SpliceBefore,
+            18 => DequeOp::SpliceAfter,
+            19 => DequeOp::Append,
+            20 => DequeOp::SplitOff(rng.below(64)),
+            21 => DequeOp::Retain(rng.below(3) as i32 + 2),
+            22 => DequeOp::Sort,
+            23 => DequeOp::Reverse,
+            24 => DequeOp::RotateLeft(rng.below(64)),
+            25 => DequeOp::Swap(rng.below(64), rng.below(64)),
+            _ => DequeOp::Clear,
+        }
+    }
+
+    fn apply(&mut self, op: &DequeOp) {
+        // 非游标操作之后游标 index 若越界就回到幽灵元素
+        let len = self.model.len();
+        let mut cursor = self.list.cursor_mut();
+        if let Some(i) = self.cursor.filter(|&i| i < len) {
+            for _ in 0..=i {
+                cursor.move_next();
+            }
+        } else {
+            self.cursor = None;
+        }
+        assert_eq!(cursor.index(), self.cursor);
+        match *op {
+            DequeOp::PushFront(v) => {
+                self.list.push_front(v);
+                self.model.push_front(v);
+            }
+            DequeOp::PushBack(v) => {
+                self.list.push_back(v);
+                self.model.push_back(v);
+            }
+            DequeOp::PopFront => {
+                assert_eq!(self.list.pop_front(), self.model.pop_front(), "pop_front");
+            }
+            DequeOp::PopBack => {
+                assert_eq!(self.list.pop_back(), self.model.pop_back(), "pop_back");
+            }
+            DequeOp::MoveNext => {
+                cursor.move_next();
+                self.cursor = match self.cursor {
+                    None if len > 0 => Some(0),
+                    Some(i) if i + 1 < len => Some(i + 1),
+                    _ => None,
+                };
+                assert_eq!(cursor.index(), self.cursor, "move_next");
+                assert_eq!(cursor.current().copied(), model_current(&self.model, self.cursor));
+            }
+            DequeOp::MovePrev => {
+                cursor.move_prev();
+                self.cursor = match self.cursor {
+                    None if len > 0 => Some(len - 1),
+                    Some(i) if i > 0 => Some(i - 1),
+                    _ => None,
+                };
+                assert_eq!(cursor.index(), self.cursor, "move_prev");
+                assert_eq!(cursor.current().copied(), model_current(&self.model, self.cursor));
+            }
+            DequeOp::InsertBefore(v) => {
+                cursor.insert_before(v);
+                match self.cursor.as_mut() {
+                    None => self.model.push_back(v),
+                    Some(i) => {
+                        self.model.insert(*i, v);
+                        *i += 1;
+                    }
+                }
+                assert_eq!(cursor.index(), self.cursor, "insert_before");
+            }
+            DequeOp::InsertAfter(v) => {
+                cursor.insert_after(v);
+                match self.cursor {
+                    None => self.model.push_front(v),
+                    Some(i) => self.model.insert(i + 1, v),
+                }
+                assert_eq!(cursor.index(), self.cursor, "insert_after");
+            }
+            DequeOp::RemoveCurrent | DequeOp::RemoveCurrentAsList => {
+                let removed = if let DequeOp::RemoveCurrent = op {
+                    cursor.remove_current()
+                } else {
+                    cursor.remove_current_as_list().map(|mut l| {
+                        assert_eq!(l.len(), 1);
+                        l.pop_front().unwrap()
+                    })
+                };
+                let expected = match self.cursor {
+                    Some(i) => self.model.remove(i),
+                    None => None,
+                };
+                assert_eq!(removed, expected, "remove_current");
+                let len = self.model.len();
+                self.cursor = self.cursor.filter(|&i| i < len);
+                assert_eq!(cursor.index(), self.cursor, "remove_current");
+            }
+            DequeOp::ReplaceCurrent(v) => {
+                let replaced = cursor.replace_current(v);
+                match self.cursor {
+                    None => assert_eq!(replaced, Err(v)),
+                    Some(i) => assert_eq!(replaced, Ok(mem::replace(&mut self.model[i], v))),
+                }
+            }
+            DequeOp::SplitBefore => {
+                let mut output = cursor.split_before();
+                let expected: VecDeque<_> = match self.cursor {
+                    None => mem::take(&mut self.model),
+                    Some(i) => {
+                        self.cursor = Some(0);
+                        self.model.drain(..i).collect()
+                    }
+                };
+                assert_eq!(cursor.index(), self.cursor, "split_before");
+                assert!(output.iter().eq(expected.iter()), "split_before output");
+                self.other.append(&mut output);
+                self.model_other.extend(expected);
+            }
+            DequeOp::SplitAfter => {
+                let mut output = cursor.split_after();
+                let expected = match self.cursor {
+                    None => mem::take(&mut self.model),
+                    Some(i) => self.model.split_off(i + 1),
+                };
+                assert_eq!(cursor.index(), self.cursor, "split_after");
+                assert!(output.iter().eq(expected.iter()), "split_after output");
+                self.other.append(&mut output);
+                self.model_other.extend(expected);
+            }
+            DequeOp::SpliceBefore => {
+                cursor.splice_before(mem::take(&mut self.other));
+                let input = mem::take(&mut self.model_other);
+                match self.cursor.as_mut() {
+                    None => self.model.extend(input),
+                    Some(i) => {
+                        let n = input.len();
+                        let rest = self.model.split_off(*i);
+                        self.model.extend(input);
+                        self.model.extend(rest);
+                        *i += n;
+                    }
+                }
+                assert_eq!(cursor.index(), self.cursor, "splice_before");
+            }
+            DequeOp::SpliceAfter => {
+                cursor.splice_after(mem::take(&mut self.other));
+                let mut input = mem::take(&mut self.model_other);
+                match self.cursor {
+                    None => {
+                        input.extend(self.model.drain(..));
+                        self.model = input;
+                    }
+                    Some(i) => {
+                        let rest = self.model.split_off(i + 1);
+                        self.model.extend(input);
+                        self.model.extend(rest);
+                    }
+                }
+                assert_eq!(cursor.index(), self.cursor, "splice_after");
+            }
+            DequeOp::Append => {
+                self.list.append(&mut self.other);
+                self.model.append(&mut self.model_other);
+            }
+            DequeOp::SplitOff(at) => {
+                let at = at % (len + 1);
+                let mut output = self.list.split_off(at);
+                let expected = self.model.split_off(at);
+                assert!(output.iter().eq(expected.iter()), "split_off output");
+                self.other.append(&mut output);
+                self.model_other.extend(expected);
+            }
+            DequeOp::Retain(m) => {
+                self.list.retain(|x| x % m != 0);
+                self.model.retain(|x| x % m != 0);
+            }
+            DequeOp::Sort => {
+                self.list.sort();
+                self.model.make_contiguous().sort();
+            }
+            DequeOp::Reverse => {
+                self.list.reverse();
+                self.model.make_contiguous().reverse();
+            }
+            DequeOp::RotateLeft(k) => {
+                let k = k % (len + 1);
+                self.list.rotate_left(k);
+                self.model.rotate_left(k);
+            }
+            DequeOp::Swap(i, j) => {
+                if len > 0 {
+                    self.list.swap(i % len, j % len);
+                    self.model.swap(i % len, j % len);
+                }
+            }
+            DequeOp::Clear => {
+                self.list.clear();
+                self.model.clear();
+            }
+        }
+        self.list.check_invariants();
+        self.other.check_invariants();
+        assert!(self.list.iter().eq(self.model.iter()), "contents differ after {:?}", op);
+        assert!(self.list.iter().rev().eq(self.model.iter().rev()), "reverse contents differ after {:?}", op);
+        assert!(self.other.iter().eq(self.model_other.iter()), "other contents differ after {:?}", op);
+        assert_eq!(self.list.len(), self.model.len());
+    }
+}
+
+/// 只有 push pop 是实现了的, 内容通过全部弹出再压回来比较
+pub(crate) struct BadDeque {
+    list: bad_safe_deque::List<i32>,
+    model: VecDeque<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum BadDequeOp {
+    PushFront(i32),
+    PushBack(i32),
+    PopFront,
+    PopBack,
+}
+
+impl Target for BadDeque {
+    type Op = BadDequeOp;
+
+    fn new() -> Self {
+        Self { list: bad_safe_deque::List::new(), model: VecDeque::new() }
+    }
+
+    fn gen_op(rng: &mut Rng) -> BadDequeOp {
+        match rng.below(4) {
+            0 => BadDequeOp::PushFront(rng.value()),
+            1 => BadDequeOp::PushBack(rng.value()),
+            2 => BadDequeOp::PopFront,
+            _ => BadDequeOp::PopBack,
+        }
+    }
+
+    fn apply(&mut self, op: &BadDequeOp) {
+        match *op {
+            BadDequeOp::PushFront(v) => {
+                self.list.push_front(v);
+                self.model.push_front(v);
+            }
+            BadDequeOp::PushBack(v) => {
+                self.list.push_back(v);
+                self.model.push_back(v);
+            }
+            BadDequeOp::PopFront => assert_eq!(self.list.pop_front(), self.model.pop_front(), "pop_front"),
+            BadDequeOp::PopBack => assert_eq!(self.list.pop_back(), self.model.pop_back(), "pop_back"),
+        }
+        let mut contents = Vec::new();
+        while let Some(v) = self.list.pop_front() {
+            contents.push(v);
+        }
+        for &v in &contents {
+            self.list.push_back(v);
+        }
+        assert!(contents.iter().eq(self.model.iter()), "contents differ after {:?}", op);
+    }
+}
+
+/// 模型是 `VecDeque` 加上空隙的位置, 空隙左边是 `left`, 右边是 `right`
+pub(crate) struct DoubleSingle {
+    list: double_single::List<i32>,
+    model: VecDeque<i32>,
+    gap: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum DoubleSingleOp {
+    PushLeft(i32),
+    PushRight(i32),
+    PopLeft,
+    PopRight,
+    GoLeft,
+    GoRight,
+    SetLeft(i32),
+}
+
+impl Target for DoubleSingle {
+    type Op = DoubleSingleOp;
+
+    fn new() -> Self {
+        Self { list: double_single::List::new(), model: VecDeque::new(), gap: 0 }
+    }
+
+    fn gen_op(rng: &mut Rng) -> DoubleSingleOp {
+        match rng.below(7) {
+            0 => DoubleSingleOp::PushLeft(rng.value()),
+            1 => DoubleSingleOp::PushRight(rng.value()),
+            2 => DoubleSingleOp::PopLeft,
+            3 => DoubleSingleOp::PopRight,
+            4 => DoubleSingleOp::GoLeft,
+            5 => DoubleSingleOp::GoRight,
+            _ => DoubleSingleOp::SetLeft(rng.value()),
+        }
+    }
+
+    fn apply(&mut self, op: &DoubleSingleOp) {
+        match *op {
+            DoubleSingleOp::PushLeft(v) => {
+                self.list.push_left(v);
+                self.model.insert(self.gap, v);
+                self.gap += 1;
+            }
+            DoubleSingleOp::PushRight(v) => {
+                self.list.push_right(v);
+                self.model.insert(self.gap, v);
+            }
+            DoubleSingleOp::PopLeft => {
+                let expected = if self.gap > 0 {
+                    self.gap -= 1;
+                    self.model.remove(self.gap)
+                } else {
+                    None
+                };
+                assert_eq!(self.list.pop_left(), expected, "pop_left");
+            }
+            DoubleSingleOp::PopRight => assert_eq!(self.list.pop_right(), self.model.remove(self.gap), "pop_right"),
+            DoubleSingleOp::GoLeft => {
+                assert_eq!(self.list.go_left(), self.gap > 0, "go_left");
+                self.gap = self.gap.saturating_sub(1);
+            }
+            DoubleSingleOp::GoRight => {
+                assert_eq!(self.list.go_right(), self.gap < self.model.len(), "go_right");
+                self.gap = (self.gap + 1).min(self.model.len());
+            }
+            DoubleSingleOp::SetLeft(v) => {
+                if let Some(left) = self.list.peek_left_mut() {
+                    *left = v;
+                }
+                if self.gap > 0 {
+                    self.model[self.gap - 1] = v;
+                }
+            }
+        }
+        assert_eq!(self.list.peek_left(), self.gap.checked_sub(1).map(|i| &self.model[i]));
+        assert_eq!(self.list.peek_right(), self.model.get(self.gap));
+        // 整体走到最左边, 再逐个走回来读出全部内容
+        let mut moved = 0;
+        while self.list.go_left() {
+            moved += 1;
+        }
+        assert_eq!(moved, self.gap, "gap position differs after {:?}", op);
+        let mut contents = Vec::new();
+        while let Some(&v) = self.list.peek_right() {
+            contents.push(v);
+            self.list.go_right();
+        }
+        assert!(contents.iter().eq(self.model.iter()), "contents differ after {:?}", op);
+        for _ in self.gap..self.model.len() {
+            self.list.go_left();
+        }
+    }
+}
+
+/// 保留每个版本和对应的模型, 操作随机选一个旧版本进行, 检查旧版本不受影响
+pub(crate) struct SharedQueue {
+    versions: Vec<(PersistentQueue<i32>, VecDeque<i32>)>,
+    current: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum SharedQueueOp {
+    PushBack(i32),
+    PopFront,
+    Checkout(usize),
+}
+
+impl Target for SharedQueue {
+    type Op = SharedQueueOp;
+
+    fn new() -> Self {
+        Self { versions: vec![(PersistentQueue::new(), VecDeque::new())], current: 0 }
+    }
+
+    fn gen_op(rng: &mut Rng) -> SharedQueueOp {
+        // 入队多一些, 队列才会变长, 旋转才会跨过很多个操作
+        match rng.below(6) {
+            0..=2 => SharedQueueOp::PushBack(rng.value()),
+            3 | 4 => SharedQueueOp::PopFront,
+            _ => SharedQueueOp::Checkout(rng.below(1 << 16)),
+        }
+    }
+
+    fn apply(&mut self, op: &SharedQueueOp) {
+        let (queue, model) = &self.versions[self.current];
+        let mut model = model.clone();
+        let queue = match *op {
+            SharedQueueOp::PushBack(v) => {
+                model.push_back(v);
+                queue.push_back(v)
+            }
+            SharedQueueOp::PopFront => match queue.pop_front() {
+                Some((elem, queue)) => {
+                    assert_eq!(Some(*elem), model.pop_front(), "pop_front");
+                    queue
+                }
+                None => {
+                    assert!(model.is_empty(), "pop_front");
+                    return;
+                }
+            },
+            SharedQueueOp::Checkout(i) => {
+                self.current = i % self.versions.len();
+                return;
+            }
+        };
+        assert_eq!(queue.len(), model.len());
+        assert_eq!(queue.front(), model.front());
+        assert!(queue.iter().eq(model.iter()), "contents differ after {:?}", op);
+        let (old, old_model) = &self.versions[self.current];
+        assert!(old.iter().eq(old_model.iter()), "old version changed after {:?}", op);
+        self.versions.push((queue, model));
+        self.current = self.versions.len() - 1;
+    }
+}
+
+pub(crate) struct SharedDeque {
+    versions: Vec<(PersistentDeque<i32>, VecDeque<i32>)>,
+    current: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum SharedDequeOp {
+    PushFront(i32),
+    PushBack(i32),
+    PopFront,
+    PopBack,
+    Checkout(usize),
+}
+
+impl Target for SharedDeque {
+    type Op = SharedDequeOp;
+
+    fn new() -> Self {
+        Self { versions: vec![(PersistentDeque::new(), VecDeque::new())], current: 0 }
+    }
+
+    fn gen_op(rng: &mut Rng) -> SharedDequeOp {
+        // push 多一些, 才能长到需要分多步重建的长度
+        match rng.below(11) {
+            0..=2 => SharedDequeOp::PushFront(rng.value()),
+            3..=5 => SharedDequeOp::PushBack(rng.value()),
+            6 | 7 => SharedDequeOp::PopFront,
+            8 | 9 => SharedDequeOp::PopBack,
+            _ => SharedDequeOp::Checkout(rng.below(1 << 16)),
+        }
+    }
+
+    fn apply(&mut self, op: &SharedDequeOp) {
+        let (deque, model) = &self.versions[self.current];
+        let mut model = model.clone();
+        let popped = |popped: Option<(&i32, PersistentDeque<i32>)>, expected: Option<i32>| match popped {
+            Some((elem, deque)) => {
+                assert_eq!(Some(*elem), expected, "{:?}", op);
+                Some(deque)
+            }
+            None => {
+                assert_eq!(expected, None, "{:?}", op);
+                None
+            }
+        };
+        let deque = match *op {
+            SharedDequeOp::PushFront(v) => {
+                model.push_front(v);
+                Some(deque.push_front(v))
+            }
+            SharedDequeOp::PushBack(v) => {
+                model.push_back(v);
+                Some(deque.push_back(v))
+            }
+            SharedDequeOp::PopFront => popped(deque.pop_front(), model.pop_front()),
+            SharedDequeOp::PopBack => popped(deque.pop_back(), model.pop_back()),
+            SharedDequeOp::Checkout(i) => {
+                self.current = i % self.versions.len();
+                None
+            }
+        };
+        let deque = match deque {
+            Some(deque) => deque,
+            None => return,
+        };
+        assert_eq!(deque.len(), model.len());
+        assert_eq!(deque.front(), model.front());
+        assert_eq!(deque.back(), model.back());
+        assert!(deque.iter().eq(model.iter()), "contents differ after {:?}", op);
+        let (old, old_model) = &self.versions[self.current];
+        assert!(old.iter().eq(old_model.iter()), "old version changed after {:?}", op);
+        self.versions.push((deque, model));
+        self.current = self.versions.len() - 1;
+    }
+}
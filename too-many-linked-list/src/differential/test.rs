@@ -0,0 +1,80 @@
+use super::targets::*;
+use super::{check, run, shrink, Rng, Target};
+
+#[test]
+fn differential_linked_stack() {
+    check::<Stack>(0..200, 200);
+}
+
+#[test]
+fn differential_unsafe_queue() {
+    check::<Queue>(0..200, 200);
+}
+
+#[test]
+fn differential_unsafe_deque() {
+    check::<Deque>(0..500, 300);
+}
+
+#[test]
+fn differential_bad_safe_deque() {
+    check::<BadDeque>(0..100, 100);
+}
+
+#[test]
+fn differential_double_single() {
+    check::<DoubleSingle>(0..200, 200);
+}
+
+#[test]
+fn differential_persistent_queue() {
+    check::<SharedQueue>(0..100, 400);
+}
+
+#[test]
+fn differential_persistent_deque() {
+    check::<SharedDeque>(0..100, 400);
+}
+
+#[test]
+fn rng_is_deterministic() {
+    let a: Vec<_> = (0..8).map({ let mut rng = Rng::new(7); move |_| rng.next_u64() }).collect();
+    let b: Vec<_> = (0..8).map({ let mut rng = Rng::new(7); move |_| rng.next_u64() }).collect();
+    assert_eq!(a, b);
+    assert_ne!(Rng::new(7).next_u64(), Rng::new(8).next_u64());
+}
+
+/// 故意写错的模型: 连续压入两个相同的值后出错
+struct Buggy {
+    last: Option<i32>,
+}
+
+impl Target for Buggy {
+    type Op = i32;
+
+    fn new() -> Self {
+        Self { last: None }
+    }
+
+    fn gen_op(rng: &mut Rng) -> i32 {
+        rng.below(4) as i32
+    }
+
+    fn apply(&mut self, op: &i32) {
+        assert_ne!(self.last.replace(*op), Some(*op), "planted bug");
+    }
+}
+
+#[test]
+fn shrink_to_minimal() {
+    let mut rng = Rng::new(1);
+    let ops: Vec<_> = (0..100).map(|_| Buggy::gen_op(&mut rng)).collect();
+    assert!(run::<Buggy>(&ops).is_err());
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let shrunk = shrink::<Buggy>(ops);
+    std::panic::set_hook(hook);
+    assert_eq!(shrunk.len(), 2);
+    assert_eq!(shrunk[0], shrunk[1]);
+    assert!(run::<Buggy>(&shrunk).unwrap_err().contains("planted bug"));
+}
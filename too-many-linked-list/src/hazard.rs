@@ -0,0 +1,229 @@
+//! 危险指针 (hazard pointer), 供无锁数据结构安全地回收节点
+//!
+//! 1. 读取共享指针前先把它登记到一个全局可见的槽位里, 登记期间别的线程不会释放它
+//! 2. 从数据结构中摘下的节点先 `retire` 到全局的待回收链表, 攒够一批后扫描所有槽位,
+//!    没有被登记的才真正释放
+//! 3. 槽位只增不减, 线程退出时归还, 留给之后的线程复用
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+/// 一个槽位, 同一时刻只属于一个 `HazardPointer`
+struct Slot {
+    hazard: AtomicPtr<u8>,
+    active: AtomicBool,
+    next: *const Slot,
+}
+
+// 槽位链表的头, 只会在头部插入, 槽位永不释放
+static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+static SLOT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// 待回收的节点
+struct Retired {
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+    next: *mut Retired,
+}
+
+static RETIRED: AtomicPtr<Retired> = AtomicPtr::new(ptr::null_mut());
+static RETIRED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// 线程退出时把缓存的槽位标记为空闲
+struct LocalSlots(Vec<&'static Slot>);
+
+impl Drop for LocalSlots {
+    fn drop(&mut self) {
+        for slot in self.0.drain(..) {
+            slot.active.store(false, Ordering::Release);
+        }
+    }
+}
+
+thread_local! {
+    static LOCAL: RefCell<LocalSlots> = const { RefCell::new(LocalSlots(Vec::new())) };
+}
+
+fn acquire_slot() -> &'static Slot {
+    // 线程退出过程中 thread_local 可能已经销毁, 此时走全局查找
+    if let Ok(Some(slot)) = LOCAL.try_with(|local| local.borrow_mut().0.pop()) {
+        return slot;
+    }
+    let mut cur = SLOTS.load(Ordering::Acquire) as *const Slot;
+    while let Some(slot) = unsafe { cur.as_ref() } {
+        if !slot.active.load(Ordering::Relaxed)
+            && slot.active.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok()
+        {
+            return slot;
+        }
+        cur = slot.next;
+    }
+    let slot = Box::into_raw(Box::new(Slot {
+        hazard: AtomicPtr::new(ptr::null_mut()),
+        active: AtomicBool::new(true),
+        next: ptr::null(),
+    }));
+    let mut head = SLOTS.load(Ordering::Relaxed);
+    loop {
+        unsafe { (*slot).next = head };
+        match SLOTS.compare_exchange_weak(head, slot, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => break,
+            Err(new) => head = new,
+        }
+    }
+    SLOT_COUNT.fetch_add(1, Ordering::Relaxed);
+    unsafe { &*slot }
+}
+
+fn release_slot(slot: &'static Slot) {
+    let cached = LOCAL.try_with(|local| local.borrow_mut().0.push(slot));
+    if cached.is_err() {
+        slot.active.store(false, Ordering::Release);
+    }
+}
+
+/// 持有一个槽位, drop 时清空并归还
+pub(crate) struct HazardPointer {
+    slot: &'static Slot,
+}
+
+impl HazardPointer {
+    pub(crate) fn new() -> Self {
+        Self { slot: acquire_slot() }
+    }
+
+    /// 登记 `src` 当前指向的对象并返回该指针, 之后直到 `reset` 或 drop, 对象都不会被释放
+    ///
+    /// 登记后重新读一次 `src`, 两次一致才说明登记时对象还没有被摘下
+    pub(crate) fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.slot.hazard.store(ptr as *mut u8, Ordering::SeqCst);
+            let again = src.load(Ordering::SeqCst);
+            if again == ptr {
+                return ptr;
+            }
+            ptr = again;
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.slot.hazard.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Drop for HazardPointer {
+    fn drop(&mut self) {
+        self.reset();
+        release_slot(self.slot);
+    }
+}
+
+/// 所有槽位中登记的非空指针, 排好序
+///
+/// 调用前摘下的对象, 如果不在其中, 之后也不会再被登记
+pub(crate) fn protected() -> Vec<*mut u8> {
+    // 与 `protect` 中的 SeqCst 配对, 摘下对象在前, 读槽位在后
+    fence(Ordering::SeqCst);
+    let mut hazards: Vec<_> = slots()
+        .map(|s| s.hazard.load(Ordering::SeqCst))
+        .filter(|hazard| !hazard.is_null())
+        .collect();
+    hazards.sort_unstable();
+    hazards
+}
+
+/// 等到没有槽位登记 `ptr` 为止, `ptr` 必须已经从数据结构中摘下
+///
+/// 之后再登记它的线程在 `protect` 中重新读时会发现它已经被摘下, 不会再访问它
+pub(crate) fn wait_unprotected<T>(ptr: *mut T) {
+    let ptr = ptr as *mut u8;
+    loop {
+        fence(Ordering::SeqCst);
+        if !slots().any(|s| s.hazard.load(Ordering::SeqCst) == ptr) {
+            return;
+        }
+        std::thread::yield_now();
+    }
+}
+
+fn slots() -> impl Iterator<Item = &'static Slot> {
+    let head = SLOTS.load(Ordering::Acquire) as *const Slot;
+    core::iter::successors(unsafe { head.as_ref() }, |s| unsafe { s.next.as_ref() })
+}
+
+/// 待回收的个数超过这个值时扫描一次, 与槽位个数成正比, 摊还下来每次回收 O(1)
+fn threshold() -> usize {
+    2 * SLOT_COUNT.load(Ordering::Relaxed) + 64
+}
+
+/// 把已经从数据结构中摘下的 `Box<T>` 交给回收器, 没有危险指针指向它时才释放
+/// # Safety
+/// `ptr` 来自 `Box::into_raw`, 已经不能再从数据结构中读到, 且只 retire 一次
+pub(crate) unsafe fn retire<T>(ptr: *mut T) {
+    unsafe fn free<T>(ptr: *mut u8) {
+        drop(Box::from_raw(ptr as *mut T));
+    }
+    let retired = Box::into_raw(Box::new(Retired {
+        ptr: ptr as *mut u8,
+        free: free::<T>,
+        next: ptr::null_mut(),
+    }));
+    push_retired(retired, retired);
+    if RETIRED_COUNT.fetch_add(1, Ordering::Relaxed) + 1 >= threshold() {
+        collect();
+    }
+}
+
+/// 把 `first..=last` 这一串待回收节点接到全局链表头部
+unsafe fn push_retired(first: *mut Retired, last: *mut Retired) {
+    let mut head = RETIRED.load(Ordering::Relaxed);
+    loop {
+        (*last).next = head;
+        match RETIRED.compare_exchange_weak(head, first, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(new) => head = new,
+        }
+    }
+}
+
+/// 扫描一次, 释放所有没有被登记的待回收节点
+///
+/// 整个链表一次性取走, 多个线程同时扫描时各自处理不相交的一批
+pub(crate) fn collect() {
+    let mut cur = RETIRED.swap(ptr::null_mut(), Ordering::Acquire);
+    if cur.is_null() {
+        return;
+    }
+    let hazards = protected();
+
+    let (mut kept_first, mut kept_last): (*mut Retired, *mut Retired) = (ptr::null_mut(), ptr::null_mut());
+    let mut freed = 0;
+    while !cur.is_null() {
+        unsafe {
+            let next = (*cur).next;
+            if hazards.binary_search(&(*cur).ptr).is_ok() {
+                (*cur).next = kept_first;
+                if kept_last.is_null() {
+                    kept_last = cur;
+                }
+                kept_first = cur;
+            } else {
+                let retired = Box::from_raw(cur);
+                (retired.free)(retired.ptr);
+                freed += 1;
+            }
+            cur = next;
+        }
+    }
+    RETIRED_COUNT.fetch_sub(freed, Ordering::Relaxed);
+    if !kept_first.is_null() {
+        unsafe { push_retired(kept_first, kept_last) };
+    }
+}
+
+/// 还没有释放的待回收节点个数, 测试用
+#[cfg(test)]
+pub(crate) fn pending() -> usize {
+    RETIRED_COUNT.load(Ordering::Relaxed)
+}
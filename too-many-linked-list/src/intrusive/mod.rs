@@ -0,0 +1,351 @@
+//! 侵入式双向链表, 链接字段 `Links` 嵌在用户的结构体里, 链表本身从不分配内存
+//!
+//! 1. 一个结构体可以有多个 `Links` 字段, 每个字段用一个 `Adapter` 描述, 同时挂在多个链表上
+//! 2. 节点和链表都必须 pin 住, 链表借用节点 `Pin<&'a T>`, 节点活得比链表长
+//! 3. 链表是带哨兵的环, 节点只凭自己的 `&Links` 就能 O(1) 摘下, 不需要知道在哪个链表的哪个位置
+//! 4. 所有修改都经由 `Cell`, 只需要共享借用, 因此链表和节点都不是 `Sync` 的
+//!
+//! # Examples
+//! ```
+//! use std::pin::pin;
+//! use too_many_linked_list::intrusive::{Links, List};
+//! use too_many_linked_list::intrusive_adapter;
+//!
+//! struct Timer {
+//!     deadline: u32,
+//!     wheel: Links,
+//!     cache: Links,
+//! }
+//! intrusive_adapter!(WheelAdapter = Timer { wheel });
+//! intrusive_adapter!(CacheAdapter = Timer { cache });
+//!
+//! let t1 = pin!(Timer { deadline: 1, wheel: Links::new(), cache: Links::new() });
+//! let t2 = Box::pin(Timer { deadline: 2, wheel: Links::new(), cache: Links::new() });
+//! let (t1, t2) = (t1.into_ref(), t2.as_ref());
+//!
+//! let wheel = pin!(List::<WheelAdapter>::new());
+//! let cache = pin!(List::<CacheAdapter>::new());
+//! let (wheel, cache) = (wheel.into_ref(), cache.into_ref());
+//! wheel.push_back(t1);
+//! wheel.push_back(t2);
+//! cache.push_front(t2);
+//!
+//! // 不知道 t2 在链表中的位置, 也能把它从时间轮上摘下
+//! assert!(t2.wheel.unlink());
+//! assert_eq!(wheel.iter().map(|t| t.deadline).collect::<Vec<_>>(), [1]);
+//! assert_eq!(cache.front().map(|t| t.deadline), Some(2));
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+use core::marker::{PhantomData, PhantomPinned};
+use core::pin::Pin;
+use core::ptr::NonNull;
+
+#[cfg(test)]
+mod test;
+
+type Link = Cell<Option<NonNull<Links>>>;
+
+/// 嵌入到用户结构体中的前后指针, 未挂在任何链表上时两者都为 `None`
+pub struct Links {
+    prev: Link,
+    next: Link,
+    // 链表的哨兵, 不在任何 `Value` 里面
+    sentinel: bool,
+    // 挂上链表后地址不能再变
+    _pin: PhantomPinned,
+}
+
+impl Links {
+    pub const fn new() -> Self {
+        Self {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            sentinel: false,
+            _pin: PhantomPinned,
+        }
+    }
+
+    const fn sentinel() -> Self {
+        Self {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            sentinel: true,
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.next.get().is_some()
+    }
+
+    /// 从所在的链表中摘下自己, O(1); 原本不在链表中时返回 `false`
+    pub fn unlink(&self) -> bool {
+        match (self.prev.take(), self.next.take()) {
+            (Some(prev), Some(next)) => {
+                // SAFETY: 挂在链表上的前后节点 (或哨兵) 都还活着
+                unsafe {
+                    prev.as_ref().next.set(Some(next));
+                    next.as_ref().prev.set(Some(prev));
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 把孤立的 `self` 挂在 `prev` 与 `next` 之间
+    unsafe fn link_between(&self, prev: NonNull<Links>, next: NonNull<Links>) {
+        let this = NonNull::from(self);
+        self.prev.set(Some(prev));
+        self.next.set(Some(next));
+        prev.as_ref().next.set(Some(this));
+        next.as_ref().prev.set(Some(this));
+    }
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 节点比链表先被销毁时 (比如链表被 `mem::forget`), 先摘下自己, 不留悬垂指针
+impl Drop for Links {
+    fn drop(&mut self) {
+        self.unlink();
+    }
+}
+
+impl fmt::Debug for Links {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Links").field("linked", &self.is_linked()).finish()
+    }
+}
+
+/// 描述 `Links` 嵌在 `Value` 的哪个字段里, 一般用 `intrusive_adapter!` 生成
+/// # Safety
+/// `links` 必须返回 `value` 内部的字段, `value` 必须是其逆运算.
+///
+/// `Value` 不能是 `Unpin` 的: 含有 `Links` 的结构体因为 `PhantomPinned` 自动是 `!Unpin`,
+/// 实现者不能再为它 `impl Unpin`. 否则 `Pin<&Value>` 不再保证地址不变,
+/// 节点可以在挂着的时候被移走, 链表中留下悬垂指针
+pub unsafe trait Adapter {
+    type Value;
+
+    fn links(value: &Self::Value) -> &Links;
+
+    /// 由 `links` 字段的地址算出所在结构体的地址
+    /// # Safety
+    /// `links` 必须是某个 `Self::Value` 中由 `Self::links` 返回的字段
+    unsafe fn value(links: NonNull<Links>) -> NonNull<Self::Value>;
+}
+
+/// 为结构体的一个 `Links` 字段生成 `Adapter`
+///
+/// 结构体不能 `impl Unpin` (见 `Adapter` 的 `# Safety`), 否则编译失败
+/// ```
+/// use too_many_linked_list::intrusive::Links;
+/// use too_many_linked_list::intrusive_adapter;
+/// struct Entry { key: u32, lru: Links }
+/// intrusive_adapter!(LruAdapter = Entry { lru });
+/// ```
+/// ```compile_fail
+/// use too_many_linked_list::intrusive::Links;
+/// use too_many_linked_list::intrusive_adapter;
+/// struct Entry { key: u32, lru: Links }
+/// impl Unpin for Entry {}
+/// intrusive_adapter!(LruAdapter = Entry { lru });
+/// ```
+#[macro_export]
+macro_rules! intrusive_adapter {
+    ($vis:vis $name:ident = $value:ty { $field:ident }) => {
+        $vis struct $name;
+
+        // `$value: Unpin` 时两个 impl 都适用, 类型推断不出 `_` 而编译失败
+        const _: () = {
+            trait AmbiguousIfUnpin<A> {
+                fn some_item() {}
+            }
+            impl<T: ?Sized> AmbiguousIfUnpin<()> for T {}
+            impl<T: ?Sized + ::core::marker::Unpin> AmbiguousIfUnpin<u8> for T {}
+            let _ = <$value as AmbiguousIfUnpin<_>>::some_item;
+        };
+
+        unsafe impl $crate::intrusive::Adapter for $name {
+            type Value = $value;
+
+            fn links(value: &$value) -> &$crate::intrusive::Links {
+                &value.$field
+            }
+
+            unsafe fn value(
+                links: ::core::ptr::NonNull<$crate::intrusive::Links>,
+            ) -> ::core::ptr::NonNull<$value> {
+                let offset = ::core::mem::offset_of!($value, $field);
+                ::core::ptr::NonNull::new_unchecked(
+                    (links.as_ptr() as *mut u8).sub(offset) as *mut $value
+                )
+            }
+        }
+    };
+}
+
+/// 带哨兵的环形链表, 必须 pin 住后才能使用
+///
+/// 新建的链表哨兵还没有连成环, 可以随意移动; 第一次挂上节点时才连成环
+pub struct List<'a, A: Adapter> {
+    head: Links,
+    // 'a 必须不变, 否则可以把短命的节点塞进长命的链表
+    _marker: PhantomData<(Cell<&'a A::Value>, A)>,
+}
+
+impl<'a, A: Adapter> List<'a, A> {
+    pub const fn new() -> Self {
+        Self {
+            head: Links::sentinel(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn head(self: Pin<&Self>) -> NonNull<Links> {
+        let head = NonNull::from(&self.get_ref().head);
+        if !self.head.is_linked() {
+            // 已经 pin 住了, 哨兵的地址不会再变
+            self.head.prev.set(Some(head));
+            self.head.next.set(Some(head));
+        }
+        head
+    }
+
+    /// 哨兵之后的第一个节点, 空链表时为 `None`
+    fn first(self: Pin<&Self>) -> Option<NonNull<Links>> {
+        let head = NonNull::from(&self.get_ref().head);
+        self.head.next.get().filter(|&node| node != head)
+    }
+
+    fn last(self: Pin<&Self>) -> Option<NonNull<Links>> {
+        let head = NonNull::from(&self.get_ref().head);
+        self.head.prev.get().filter(|&node| node != head)
+    }
+
+    pub fn is_empty(self: Pin<&Self>) -> bool {
+        self.first().is_none()
+    }
+
+    /// 遍历计数, O(n)
+    pub fn len(self: Pin<&Self>) -> usize {
+        self.iter().count()
+    }
+
+    /// # Panics
+    /// `value` 已经通过同一个字段挂在某个链表上时 panic
+    pub fn push_front(self: Pin<&Self>, value: Pin<&'a A::Value>) {
+        let links = A::links(value.get_ref());
+        assert!(!links.is_linked(), "value is already linked");
+        let head = self.head();
+        unsafe {
+            let next = self.head.next.get().unwrap_unchecked();
+            links.link_between(head, next);
+        }
+    }
+
+    /// # Panics
+    /// `value` 已经通过同一个字段挂在某个链表上时 panic
+    pub fn push_back(self: Pin<&Self>, value: Pin<&'a A::Value>) {
+        let links = A::links(value.get_ref());
+        assert!(!links.is_linked(), "value is already linked");
+        let head = self.head();
+        unsafe {
+            let prev = self.head.prev.get().unwrap_unchecked();
+            links.link_between(prev, head);
+        }
+    }
+
+    pub fn front(self: Pin<&Self>) -> Option<Pin<&'a A::Value>> {
+        self.first().map(|links| unsafe { Self::value(links) })
+    }
+
+    pub fn back(self: Pin<&Self>) -> Option<Pin<&'a A::Value>> {
+        self.last().map(|links| unsafe { Self::value(links) })
+    }
+
+    pub fn pop_front(self: Pin<&Self>) -> Option<Pin<&'a A::Value>> {
+        self.first().map(|links| unsafe {
+            links.as_ref().unlink();
+            Self::value(links)
+        })
+    }
+
+    pub fn pop_back(self: Pin<&Self>) -> Option<Pin<&'a A::Value>> {
+        self.last().map(|links| unsafe {
+            links.as_ref().unlink();
+            Self::value(links)
+        })
+    }
+
+    /// 摘下所有节点
+    pub fn clear(self: Pin<&Self>) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(self: Pin<&Self>) -> Iter<'_, 'a, A> {
+        Iter {
+            next: self.first(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 挂在链表上的节点都是以 `Pin<&'a A::Value>` 放进来的
+    unsafe fn value(links: NonNull<Links>) -> Pin<&'a A::Value> {
+        Pin::new_unchecked(A::value(links).as_ref())
+    }
+}
+
+impl<'a, A: Adapter> Default for List<'a, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 链表销毁时摘下所有节点, 节点可以继续挂到别的链表上
+impl<'a, A: Adapter> Drop for List<'a, A> {
+    fn drop(&mut self) {
+        // SAFETY: 连成环之后链表一定是 pin 住的, drop 之前地址不会变
+        let this = unsafe { Pin::new_unchecked(&*self) };
+        this.clear();
+    }
+}
+
+impl<'a, A: Adapter> fmt::Debug for List<'a, A>
+where
+    A::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // SAFETY: 只读遍历, 不会连成环也不会移动
+        let this = unsafe { Pin::new_unchecked(self) };
+        f.debug_list().entries(this.iter()).finish()
+    }
+}
+
+/// 遍历时每次现读 `next`, 遇到任何一个哨兵就结束
+///
+/// 遍历中途节点可能被摘下, 甚至挂到别的链表上, 所以不能只和本链表的哨兵比较,
+/// 否则会把别的链表的哨兵当成 `Value`
+pub struct Iter<'l, 'a, A: Adapter> {
+    next: Option<NonNull<Links>>,
+    _marker: PhantomData<&'l List<'a, A>>,
+}
+
+impl<'l, 'a, A: Adapter> Iterator for Iter<'l, 'a, A> {
+    type Item = Pin<&'a A::Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let links = self.next?;
+        unsafe {
+            self.next = links.as_ref().next.get().filter(|node| !node.as_ref().sentinel);
+            Some(List::<A>::value(links))
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::{Links, List};
+use std::pin::{pin, Pin};
+
+struct Timer {
+    deadline: u32,
+    wheel: Links,
+    cache: Links,
+}
+crate::intrusive_adapter!(WheelAdapter = Timer { wheel });
+crate::intrusive_adapter!(CacheAdapter = Timer { cache });
+
+fn timer(deadline: u32) -> Pin<Box<Timer>> {
+    Box::pin(Timer {
+        deadline,
+        wheel: Links::new(),
+        cache: Links::new(),
+    })
+}
+
+fn deadlines<A: super::Adapter<Value = Timer>>(list: Pin<&List<'_, A>>) -> Vec<u32> {
+    list.iter().map(|t| t.deadline).collect()
+}
+
+#[test]
+fn test_basic() {
+    let timers: Vec<_> = (0..5).map(timer).collect();
+    let list = pin!(List::<WheelAdapter>::new());
+    let list = list.into_ref();
+    assert!(list.is_empty());
+    assert_eq!(list.pop_front().map(|t| t.deadline), None);
+
+    for t in &timers[1..4] {
+        list.push_back(t.as_ref());
+    }
+    list.push_front(timers[0].as_ref());
+    list.push_back(timers[4].as_ref());
+    assert_eq!(deadlines(list), [0, 1, 2, 3, 4]);
+    assert_eq!(list.len(), 5);
+    assert_eq!(list.front().map(|t| t.deadline), Some(0));
+    assert_eq!(list.back().map(|t| t.deadline), Some(4));
+
+    assert_eq!(list.pop_front().map(|t| t.deadline), Some(0));
+    assert_eq!(list.pop_back().map(|t| t.deadline), Some(4));
+    assert!(!timers[0].wheel.is_linked());
+    assert_eq!(deadlines(list), [1, 2, 3]);
+
+    // 摘下的节点可以重新挂上
+    list.push_back(timers[0].as_ref());
+    assert_eq!(deadlines(list), [1, 2, 3, 0]);
+    list.clear();
+    assert!(list.is_empty());
+    assert!(timers.iter().all(|t| !t.wheel.is_linked()));
+}
+
+#[test]
+fn test_unlink_self() {
+    let timers: Vec<_> = (0..4).map(timer).collect();
+    let list = pin!(List::<WheelAdapter>::new());
+    let list = list.into_ref();
+    for t in &timers {
+        list.push_back(t.as_ref());
+    }
+
+    assert!(timers[2].wheel.unlink());
+    assert!(!timers[2].wheel.unlink());
+    assert_eq!(deadlines(list), [0, 1, 3]);
+    assert!(timers[0].wheel.unlink());
+    assert!(timers[3].wheel.unlink());
+    assert_eq!(deadlines(list), [1]);
+    assert!(timers[1].wheel.unlink());
+    assert!(list.is_empty());
+    assert_eq!(list.back().map(|t| t.deadline), None);
+}
+
+#[test]
+fn test_iter_moved_to_other_list() {
+    let (x, y) = (timer(0), timer(1));
+    let a = pin!(List::<WheelAdapter>::new());
+    let b = pin!(List::<WheelAdapter>::new());
+    let (a, b) = (a.into_ref(), b.into_ref());
+    a.push_back(x.as_ref());
+    a.push_back(y.as_ref());
+
+    let mut it = a.iter();
+    assert_eq!(it.next().map(|t| t.deadline), Some(0));
+    // 迭代器指向的下一个节点被挪到另一个链表的末尾
+    assert!(y.wheel.unlink());
+    b.push_back(y.as_ref());
+    assert_eq!(it.next().map(|t| t.deadline), Some(1));
+    // 不会把 b 的哨兵当成节点
+    assert!(it.next().is_none());
+    assert_eq!(deadlines(a), [0]);
+    assert_eq!(deadlines(b), [1]);
+}
+
+#[test]
+fn test_multiple_lists() {
+    let timers: Vec<_> = (0..4).map(timer).collect();
+    let wheel = pin!(List::<WheelAdapter>::new());
+    let cache = pin!(List::<CacheAdapter>::new());
+    let (wheel, cache) = (wheel.into_ref(), cache.into_ref());
+    for t in &timers {
+        wheel.push_back(t.as_ref());
+        cache.push_front(t.as_ref());
+    }
+    assert_eq!(deadlines(wheel), [0, 1, 2, 3]);
+    assert_eq!(deadlines(cache), [3, 2, 1, 0]);
+
+    timers[1].wheel.unlink();
+    timers[2].cache.unlink();
+    assert_eq!(deadlines(wheel), [0, 2, 3]);
+    assert_eq!(deadlines(cache), [3, 1, 0]);
+}
+
+#[test]
+#[should_panic(expected = "already linked")]
+fn test_double_link() {
+    let t = timer(0);
+    let a = pin!(List::<WheelAdapter>::new());
+    let b = pin!(List::<WheelAdapter>::new());
+    a.as_ref().push_back(t.as_ref());
+    b.as_ref().push_back(t.as_ref());
+}
+
+#[test]
+fn test_drop() {
+    let timers: Vec<_> = (0..3).map(timer).collect();
+    {
+        let list = pin!(List::<WheelAdapter>::new());
+        for t in &timers {
+            list.as_ref().push_back(t.as_ref());
+        }
+    }
+    assert!(timers.iter().all(|t| !t.wheel.is_linked()));
+
+    // 链表被 forget 后节点先销毁, 节点会把自己从泄漏的哨兵上摘下
+    let keep = timer(9);
+    let t = timer(1);
+    let list = Box::pin(List::<WheelAdapter>::new());
+    list.as_ref().push_back(keep.as_ref());
+    list.as_ref().push_back(t.as_ref());
+    std::mem::forget(list);
+    drop(t);
+    assert!(keep.wheel.unlink());
+}
@@ -1,12 +1,25 @@
-//#![feature(specialization)]
-#![feature(min_specialization)]
-#![feature(rustc_attrs)]
+// 关闭 `std` feature 时只依赖 `alloc`, 测试总是链接 std
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 pub mod bad;
 
 pub mod linked_stack;
 pub mod shared_linked_list;
+#[cfg(feature = "std")]
 pub mod bad_safe_deque;
 pub mod unsafe_queue;
 pub mod unsafe_deque;
+pub mod intrusive;
+#[cfg(feature = "std")]
+pub mod lru_cache;
 pub mod silly_lists;
+#[cfg(feature = "std")]
+pub mod lockfree_queue;
+
+#[cfg(feature = "std")]
+pub(crate) mod hazard;
+
+#[cfg(all(test, feature = "std"))]
+mod differential;
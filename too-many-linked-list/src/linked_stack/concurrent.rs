@@ -0,0 +1,188 @@
+//! 无锁并发栈 (Treiber stack), 节点与 `LinkedStack` 完全相同
+//!
+//! 1. 栈顶是一个原子指针, push pop 都是对它 CAS
+//! 2. pop 和 `peek_with` 先用 `hazard` 登记栈顶再访问它; 摘下节点的线程等到没有别的线程登记着它,
+//!    才移走元素并释放节点. 登记着的节点不会被释放, 地址也就不会被复用, 从而避免 ABA
+//! 3. 摘下之后再登记的线程在 `protect` 中会发现栈顶已经变了, 不会访问它, 所以只需等待一次.
+//!    `pop` 通常只等别的 `pop` 读完 next; 只有 `peek_with` 正在读同一个元素时才要等它的闭包返回
+//! 4. 节点在栈上时只通过裸指针读写 `next`, 不产生 `Box`; `take_all` 把整条链原样交给 `LinkedStack`
+//!
+//! # Examples
+//! ```
+//! use std::thread;
+//! use too_many_linked_list::linked_stack::concurrent::AtomicStack;
+//!
+//! let stack = AtomicStack::new();
+//! thread::scope(|s| {
+//!     for t in 0..4 {
+//!         let stack = &stack;
+//!         s.spawn(move || {
+//!             for i in 0..100 {
+//!                 stack.push(t * 100 + i);
+//!             }
+//!         });
+//!     }
+//! });
+//! assert_eq!(stack.peek_with(|x| x % 100), Some(99));
+//! let mut all: Vec<_> = stack.take_all().into_iter().collect();
+//! all.sort();
+//! assert_eq!(all, (0..400).collect::<Vec<_>>());
+//! assert!(stack.is_empty());
+//! ```
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+
+use crate::hazard::{self, HazardPointer};
+use super::{LinkedStack, Node};
+
+pub struct AtomicStack<T> {
+    head: AtomicPtr<Node<T>>,
+    // 入栈后加, 出栈后减, 并发时可能短暂为负
+    len: AtomicIsize,
+    _boo: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for AtomicStack<T> {}
+// `peek_with` 让多个线程同时读同一个元素
+unsafe impl<T: Send + Sync> Sync for AtomicStack<T> {}
+
+/// 节点的 `next`, 当作裸指针读写; `Option<Box<_>>` 与裸指针布局相同, 空指针即 `None`
+unsafe fn next_of<T>(node: *mut Node<T>) -> *mut *mut Node<T> {
+    ptr::addr_of_mut!((*node).next).cast()
+}
+
+/// 把一串已经摘下的节点原样交给 `LinkedStack`, 不重新分配
+/// # Safety
+/// 链上的节点都来自 `Box::into_raw`, 且没有别的线程登记着它们
+unsafe fn into_stack<T>(head: *mut Node<T>) -> LinkedStack<T> {
+    let mut len = 0;
+    let mut cur = head;
+    while !cur.is_null() {
+        len += 1;
+        cur = *next_of(cur);
+    }
+    LinkedStack {
+        head: NonNull::new(head).map(|node| Box::from_raw(node.as_ptr())),
+        len,
+    }
+}
+
+impl<T> AtomicStack<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            len: AtomicIsize::new(0),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn push(&self, elem: T) {
+        let new = Box::into_raw(Box::new(Node { elem, next: None }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // new 还没有发布, 可以随意改
+            unsafe { *next_of(new) = head };
+            match self.head.compare_exchange_weak(head, new, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(cur) => head = cur,
+            }
+        }
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 栈为空时返回 `None`
+    pub fn pop(&self) -> Option<T> {
+        let mut hp = HazardPointer::new();
+        loop {
+            let head = hp.protect(&self.head);
+            if head.is_null() {
+                return None;
+            }
+            // SAFETY: head 已登记, 不会被回收; 节点入栈后 next 不再改变
+            let next = unsafe { *next_of(head) };
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                hp.reset();
+                // 等别的线程放开 head: 即将 CAS 失败的 `pop`, 正在读元素的 `peek_with`
+                hazard::wait_unprotected(head);
+                self.len.fetch_sub(1, Ordering::Relaxed);
+                // SAFETY: 只有赢得 CAS 的线程摘下了它, 现在也没有别的线程能访问它了;
+                // 按 `ManuallyDrop` 释放, 不再 drop 已经移走的 elem, 也不 drop next 指向的节点
+                unsafe {
+                    let elem = ptr::read(&(*head).elem);
+                    drop(Box::from_raw(head as *mut ManuallyDrop<Node<T>>));
+                    return Some(elem);
+                }
+            }
+        }
+    }
+
+    /// 用栈顶元素调用 `f`, 栈为空时返回 `None`
+    ///
+    /// `f` 执行期间, 弹出这个元素的 `pop` 或 `take_all` 会等它返回; 所以不要在 `f` 中
+    /// pop 或 `take_all` 同一个栈, 那样会等待自己而死锁
+    pub fn peek_with<R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        let mut hp = HazardPointer::new();
+        let head = hp.protect(&self.head);
+        if head.is_null() {
+            return None;
+        }
+        // SAFETY: 登记期间摘下 head 的线程会等我们读完, 才移走元素并释放节点
+        Some(f(unsafe { &(*head).elem }))
+    }
+
+    /// 一次取走所有元素, 节点原样交给 `LinkedStack`, 不重新分配, 顺序不变
+    ///
+    /// 取走后还要等其他线程放开登记着的节点 (即将 CAS 失败的 `pop`, 正在读的 `peek_with`)
+    pub fn take_all(&self) -> LinkedStack<T> {
+        let head = self.head.swap(ptr::null_mut(), Ordering::AcqRel);
+        let hazards = hazard::protected();
+        let mut cur = head;
+        while !cur.is_null() {
+            if hazards.binary_search(&(cur as *mut u8)).is_ok() {
+                hazard::wait_unprotected(cur);
+            }
+            // SAFETY: 链已经摘下, 其他线程只会读它
+            cur = unsafe { *next_of(cur) };
+        }
+        // SAFETY: 不再有线程登记着链上的节点, 之后登记的也会发现栈顶已经变了
+        let stack = unsafe { into_stack(head) };
+        self.len.fetch_sub(stack.len() as isize, Ordering::Relaxed);
+        stack
+    }
+
+    /// 并发修改时只是一个近似值
+    pub fn len_approx(&self) -> usize {
+        self.len.load(Ordering::Relaxed).max(0) as usize
+    }
+
+    /// 并发修改时只是一个瞬间的快照
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Default for AtomicStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for AtomicStack<T> {
+    fn drop(&mut self) {
+        // 独占访问, 没有线程登记着栈上的节点; 交给 `LinkedStack` 循环释放
+        drop(unsafe { into_stack(*self.head.get_mut()) });
+    }
+}
+
+impl<T> fmt::Debug for AtomicStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicStack").field("len_approx", &self.len_approx()).finish()
+    }
+}
@@ -2,16 +2,16 @@ use super::*;
 
 pub type Iter<'a, T> = IterMy<'a, T>;
 impl<T> LinkedStack<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter::new(self)
     }
-    pub fn iter_my(&self) -> IterMy<T> {
+    pub fn iter_my(&self) -> IterMy<'_, T> {
         IterMy::new(self)
     }
-    pub fn iter_book(&self) -> IterBook<T> {
+    pub fn iter_book(&self) -> IterBook<'_, T> {
         IterBook::new(self)
     }
-    pub fn iter_verbose(&self) -> IterVerbose<T> {
+    pub fn iter_verbose(&self) -> IterVerbose<'_, T> {
         IterVerbose::new(self)
     }
 }
@@ -30,7 +30,7 @@ impl<'a, T> Iterator for IterMy<'a, T> {
 }
 
 impl<'a, T> IterMy<'a, T> {
-    pub fn new(list: &LinkedStack<T>) -> IterMy<T> {
+    pub fn new(list: &LinkedStack<T>) -> IterMy<'_, T> {
         IterMy(&list.head)
     }
     pub fn peek(&self) -> Option<&T> {
@@ -66,7 +66,7 @@ impl<'a, T> Iterator for IterBook<'a, T> {
 }
 
 impl<'a, T> IterBook<'a, T> {
-    pub fn new(list: &LinkedStack<T>) -> IterBook<T> {
+    pub fn new(list: &LinkedStack<T>) -> IterBook<'_, T> {
         IterBook{ next: list.head.as_deref() }
     }
     pub fn peek(&self) -> Option<&T> {
@@ -97,7 +97,7 @@ impl<'a, T> Iterator for IterVerbose<'a, T> {
 }
 
 impl<'a, T> IterVerbose<'a, T> {
-    pub fn new(list: &LinkedStack<T>) -> IterVerbose<T> {
+    pub fn new(list: &LinkedStack<T>) -> IterVerbose<'_, T> {
         IterVerbose{ next: &list.head }
     }
     pub fn peek(&self) -> Option<&T> {
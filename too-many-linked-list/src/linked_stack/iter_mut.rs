@@ -2,25 +2,31 @@ use super::*;
 
 pub type IterMut<'a, T> = IterMutMy<'a, T>;
 impl<T> LinkedStack<T> {
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut::new(self)
     }
-    pub fn iter_mut_book(&mut self) -> IterMutBook<T> {
+    pub fn iter_mut_book(&mut self) -> IterMutBook<'_, T> {
         IterMutBook::new(self)
     }
-    pub fn iter_mut_my(&mut self) -> IterMutMy<T> {
+    pub fn iter_mut_my(&mut self) -> IterMutMy<'_, T> {
         IterMutMy::new(self)
     }
 }
 
 // 文章的写法, 原 LinkedStack 空, iter 取完后, 为空, 断开所有借用
-pub struct IterMutBook<'a, T>(Option<&'a mut Node<T>>);
+// 另借着 LinkedStack 的 len, 改动链时维护它; index 是当前节点的下标
+pub struct IterMutBook<'a, T> {
+    node: Option<&'a mut Node<T>>,
+    len: &'a mut usize,
+    index: usize,
+}
 
 impl<'a, T> Iterator for IterMutBook<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.take().map(|node| {
-            self.0 = node.next.as_deref_mut();
+        self.node.take().map(|node| {
+            self.node = node.next.as_deref_mut();
+            self.index += 1;
             &mut node.elem
         })
     }
@@ -28,24 +34,35 @@ impl<'a, T> Iterator for IterMutBook<'a, T> {
 
 impl<'a, T> IterMutBook<'a, T> {
     pub fn new(list: &'a mut LinkedStack<T>) -> IterMutBook<'a, T> {
-        IterMutBook(list.head.as_mut().map(|node| node.as_mut()))
+        IterMutBook {
+            node: list.head.as_mut().map(|node| node.as_mut()),
+            len: &mut list.len,
+            index: 0,
+        }
     }
     pub fn peek(&self) -> Option<&T> {
-        self.0.as_ref().map(|node| &node.elem)
+        self.node.as_ref().map(|node| &node.elem)
     }
     pub fn peek_mut(&mut self) -> Option<&mut T> {
-        self.0.as_mut().map(|node| &mut node.elem)
+        self.node.as_mut().map(|node| &mut node.elem)
     }
     pub fn split_after(&mut self) -> LinkedStack<T> {
-        LinkedStack { 
-            head: self.0.as_mut().and_then(|node| node.next.take())
+        match self.node.as_mut() {
+            None => LinkedStack::new(),
+            Some(node) => {
+                let rest = *self.len - self.index - 1;
+                *self.len = self.index + 1;
+                LinkedStack { head: node.next.take(), len: rest }
+            }
         }
     }
     // 不能插入空串 理论上保存 &mut link 可以
     pub fn insert_after(&mut self, elem: T) -> Option<()> {
-        self.0.as_mut().map(|node| {
+        let len = &mut *self.len;
+        self.node.as_mut().map(|node| {
             let next = node.next.take();
             node.next = Some(Box::new(Node{ elem, next }));
+            *len += 1;
         })
     }
     // 删掉下一个节点
@@ -53,26 +70,32 @@ impl<'a, T> IterMutBook<'a, T> {
         todo!()
     }
     // split + join
-    pub fn replace_after(&mut self, join: LinkedStack<T>) -> LinkedStack<T> {
+    pub fn replace_after(&mut self, _join: LinkedStack<T>) -> LinkedStack<T> {
         todo!()
     }
     // 合并
-    pub fn insert_list_after(&mut self, join: LinkedStack<T>) -> Option<()> {
+    pub fn insert_list_after(&mut self, _join: LinkedStack<T>) -> Option<()> {
         todo!()
     }
 }
 
 // 始终有一个借用到 LinkedStack 内部, 阻止其 drop
-pub struct IterMutMy<'a, T>(&'a mut Link<T>);
+// index 是 link 之前的节点个数
+pub struct IterMutMy<'a, T> {
+    link: &'a mut Link<T>,
+    len: &'a mut usize,
+    index: usize,
+}
 // 应该可以实现 insert_at 空串可插入 走完也可插入
 impl<'a, T> Iterator for IterMutMy<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.0 {
+        match self.link {
             None => None,
             Some(node) => unsafe {
                 let node_ptr: *mut Node<T> = node.as_mut();
-                self.0 = &mut (*node_ptr).next;
+                self.link = &mut (*node_ptr).next;
+                self.index += 1;
                 Some(&mut (*node_ptr).elem)
             }
         }
@@ -81,53 +104,65 @@ impl<'a, T> Iterator for IterMutMy<'a, T> {
 
 impl<'a, T> IterMutMy<'a, T> {
     pub fn new(list: &'a mut LinkedStack<T>) -> IterMutMy<'a, T> {
-        IterMutMy(&mut list.head)
+        IterMutMy {
+            link: &mut list.head,
+            len: &mut list.len,
+            index: 0,
+        }
     }
     /// IterMut.next 的另一种写法, 要在闭包内修改, 先 take 再还回去
+    #[allow(dead_code)]
     fn next_map(&mut self) -> Option<&mut T> {
-        self.0.take().map(|node| unsafe {
+        self.link.take().map(|node| unsafe {
             let node_ptr: *mut Node<T> = Box::into_raw(node);
-            *self.0 = Some(Box::from_raw(node_ptr));
-            self.0 = &mut (*node_ptr).next;
+            *self.link = Some(Box::from_raw(node_ptr));
+            self.link = &mut (*node_ptr).next;
+            self.index += 1;
             &mut (*node_ptr).elem
         })
     }
     pub fn peek(&self) -> Option<&T> {
-        self.0.as_deref().map(|node| &node.elem)
+        self.link.as_deref().map(|node| &node.elem)
     }
     pub fn peek_mut(&mut self) -> Option<&mut T> {
-        self.0.as_deref_mut().map(|node| &mut node.elem)
+        self.link.as_deref_mut().map(|node| &mut node.elem)
     }
     pub fn split_at(&mut self) -> LinkedStack<T> {
+        let rest = *self.len - self.index;
+        *self.len = self.index;
         LinkedStack {
-            head: self.0.take()
+            head: self.link.take(),
+            len: rest,
         }
     }
     pub fn insert_at(&mut self, elem: T) {
-        *self.0 = Some(Box::new(Node {
+        *self.link = Some(Box::new(Node {
             elem,
-            next: self.0.take(),
-        }))
+            next: self.link.take(),
+        }));
+        *self.len += 1;
     }
     // 删掉当前节点
     pub fn delete_at(&mut self) -> Option<T> {
         todo!()
     }
     // split + join
-    pub fn replace_at(&mut self, join: LinkedStack<T>) -> LinkedStack<T> {
+    pub fn replace_at(&mut self, _join: LinkedStack<T>) -> LinkedStack<T> {
         todo!()
     }
     // 合并
-    pub fn insert_list_at(&mut self, join: LinkedStack<T>) -> Option<()> {
+    pub fn insert_list_at(&mut self, _join: LinkedStack<T>) -> Option<()> {
         todo!()
     }
+    #[allow(dead_code)]
     fn next_verbose(&mut self) -> Option<&'a mut T> {
-        match self.0 {
+        match self.link {
             None => None,
             Some(node) => {
                 unsafe {
                     let node_ptr: *mut Node<T> = node.as_mut();
-                    self.0 = &mut (*node_ptr).next;
+                    self.link = &mut (*node_ptr).next;
+                    self.index += 1;
                     Some(&mut (*node_ptr).elem)
                 }
             }
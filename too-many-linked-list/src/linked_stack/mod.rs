@@ -1,6 +1,12 @@
 //! [2. A Bad Stack](http://rust-unofficial.github.io/too-many-lists/first-final.html)
 //! [3. An Ok Stack](http://rust-unofficial.github.io/too-many-lists/second-final.html)
 //! 附加 peek_back push_back pop_back 此三方法循环或递归找队尾, 效率极低, pop_back 用了 unsafe
+//! 需要频繁操作队尾时用 `TailedStack`, 两端都是 O(1)
+//! 多线程共享时用 `AtomicStack`, 无锁, 可以一次取出为 `LinkedStack`
+
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use alloc::boxed::Box;
 
 #[derive(Debug)]
 pub(crate) struct Node<T> {
@@ -19,47 +25,37 @@ pub(crate) type Link<T> = Option<Box<Node<T>>>;
 ///     //let list_long_new: LinkedStack<&'long i32> = list_short; // 证明逆变
 /// }
 /// ```
-pub struct LinkedStack<T: LinkedStackItem> {
+pub struct LinkedStack<T> {
     head: Link<T>,
     //tail
+    // 缓存的元素个数, 所有改动链的地方 (包括 iter_mut 的 split/insert) 都要维护
+    len: usize,
 }
 
 mod iter;
 mod into_iter;
 mod iter_mut;
+pub mod tailed;
+#[cfg(feature = "std")]
+pub mod concurrent;
+
+pub use self::tailed::TailedStack;
+#[cfg(feature = "std")]
+pub use self::concurrent::AtomicStack;
 
 #[cfg(test)]
 mod test;
-
-pub trait LinkedStackItem {
-    fn drop_recursive() -> bool;
-}
-impl<T> LinkedStackItem for T {
-    default fn drop_recursive() -> bool {
-        false
-    }
-}
+#[cfg(feature = "serde")]
+mod serde;
 
 // 循环 drop , 不这样做的话, 默认的 Drop 是递归的, 元素多时会爆栈
-impl<T: LinkedStackItem> Drop for LinkedStack<T> {
-    default fn drop(&mut self) {
-        if T::drop_recursive() {
-            return;
-        }
-        while let Some(_) = self.pop() {}
+// 递归 drop 的对照见测试中的 `RecursiveDropStack`
+impl<T> Drop for LinkedStack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
     }
 }
 
-// 泛型特化 测试用 强制使用默认的递归 drop
-// 不能用了, 至少在 rustc 1.72.0-nightly (8084f397c 2023-06-25)
-#[rustc_specialization_trait] 
-pub trait LinkedStackRecursionDrop {}
-/*
-impl<T: LinkedStackRecursionDrop> Drop for LinkedStack<T> {
-    fn drop(&mut self) {}
-}
-*/
-
 impl<T> LinkedStack<T> {
     /// Create a new empty LinkedStack.
     /// # Examples
@@ -69,7 +65,7 @@ impl<T> LinkedStack<T> {
     /// assert_eq!(s.iter().collect::<Vec<&i32>>(), [&0i32;0]);
     /// ```
     pub fn new() -> Self {
-        Self { head: None }
+        Self { head: None, len: 0 }
     }
 
     /// # Examples
@@ -102,6 +98,7 @@ impl<T> LinkedStack<T> {
     /// ```
     pub fn push(&mut self, elem: T) {
         self.head = Some(Box::new(Node{ elem, next: self.head.take() }));
+        self.len += 1;
     }
 
     /// # Examples
@@ -117,6 +114,7 @@ impl<T> LinkedStack<T> {
     pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|node| {
             self.head = node.next;
+            self.len -= 1;
             node.elem
         })
     }
@@ -156,7 +154,8 @@ impl<T> LinkedStack<T> {
         while let Some(ref mut b) = link {
             link = &mut b.next;
         }
-        *link = Some(Box::new(Node{ elem, next: None }))
+        *link = Some(Box::new(Node{ elem, next: None }));
+        self.len += 1;
     }
 
     /// # Examples
@@ -181,6 +180,7 @@ impl<T> LinkedStack<T> {
                         node_ptr = node.as_mut();
                     }
                 }
+                self.len -= 1;
                 link.take().map(|node| node.elem)
             }
         }
@@ -189,26 +189,181 @@ impl<T> LinkedStack<T> {
     pub(crate) fn push_node(&mut self, mut node: Box<Node<T>>) {
         node.next = self.head.take();
         self.head = Some(node);
+        self.len += 1;
     }
 
     pub(crate) fn pop_node(&mut self) -> Option<Box<Node<T>>> {
         self.head.take().map(|mut node| {
             self.head = node.next.take();
+            self.len -= 1;
             node
         })
     }
+
+    /// 元素个数, O(1)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// 逐个 pop, 不会递归 drop
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|elem| elem == x)
+    }
+
+    /// 原地反转, 只改节点的 next, O(n)
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::linked_stack::*;
+    /// let mut s: LinkedStack<_> = (1..=3).collect();
+    /// s.reverse();
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [&3, &2, &1]);
+    /// ```
+    pub fn reverse(&mut self) {
+        let mut oldhead = self.head.take();
+        let mut head = None;
+        while let Some(mut node) = oldhead.take() {
+            oldhead = node.next;
+            node.next = head.take();
+            head = Some(node);
+        }
+        self.head = head;
+    }
+
+    /// 把 `other` 的全部节点接在栈底之后, `other` 变为空; 要走到栈底, O(len)
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::linked_stack::*;
+    /// let mut a: LinkedStack<_> = (1..=2).collect();
+    /// let mut b: LinkedStack<_> = (3..=4).collect();
+    /// a.append(&mut b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [&1, &2, &3, &4]);
+    /// assert_eq!(a.len(), 4);
+    /// assert!(b.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        *self.tail_link() = other.head.take();
+        self.len += core::mem::replace(&mut other.len, 0);
+    }
+
+    /// 在第 `at` 个元素处断开, 返回从栈顶数第 `at` 个及以下的元素, 自己留下上面 `at` 个, O(at)
+    /// # Panics
+    /// `at > len` 时 panic
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::linked_stack::*;
+    /// let mut s: LinkedStack<_> = (1..=5).collect();
+    /// let rest = s.split_off(2);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [&1, &2]);
+    /// assert_eq!(rest.iter().collect::<Vec<_>>(), [&3, &4, &5]);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "split index {} out of bounds for length {}", at, self.len);
+        let mut link = &mut self.head;
+        for _ in 0..at {
+            // at <= len, 走不到链表之外
+            link = &mut link.as_mut().unwrap().next;
+        }
+        let rest = Self { head: link.take(), len: self.len - at };
+        self.len = at;
+        rest
+    }
+
+    /// 只保留 `f` 返回 true 的元素, 按从栈顶到栈底的顺序对每个元素调用一次
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::linked_stack::*;
+    /// let mut s: LinkedStack<_> = (1..=6).collect();
+    /// s.retain(|x| x % 2 == 0);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [&2, &4, &6]);
+    /// assert_eq!(s.len(), 3);
+    /// ```
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut link = &mut self.head;
+        while let Some(keep) = link.as_ref().map(|node| f(&node.elem)) {
+            if keep {
+                link = &mut link.as_mut().unwrap().next;
+            } else {
+                let node = link.take().unwrap();
+                *link = node.next;
+                self.len -= 1;
+            }
+        }
+    }
+
+    /// 栈底节点的 next, 空栈时是 head
+    fn tail_link(&mut self) -> &mut Link<T> {
+        let mut link = &mut self.head;
+        while let Some(node) = link {
+            link = &mut node.next;
+        }
+        link
+    }
+}
+
+impl<T> Default for LinkedStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-/// 反转
-fn reverse<T>(ls: &mut LinkedStack<T>) {
-    let mut oldhead = ls.head.take();
-    let mut head = None;
-    while let Some(mut node) = oldhead.take() {
-        oldhead = node.next;
-        node.next = head.take();
-        head = Some(node);
+/// 逐个复制, 记住队尾按顺序接上, 不递归
+impl<T: Clone> Clone for LinkedStack<T> {
+    fn clone(&self) -> Self {
+        let mut stack = Self::new();
+        stack.extend(self.iter().cloned());
+        stack
+    }
+}
+
+/// 依次接在栈底之后, 与逐个 `push_back` 的结果相同, 但只找一次栈底
+impl<T> Extend<T> for LinkedStack<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut len = self.len;
+        let mut tail = self.tail_link();
+        for elem in iter {
+            let node = tail.insert(Box::new(Node { elem, next: None }));
+            tail = &mut node.next;
+            len += 1;
+        }
+        self.len = len;
+    }
+}
+
+/// 第一个元素在栈顶
+impl<T> FromIterator<T> for LinkedStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = Self::new();
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedStack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for LinkedStack<T> {}
+
+impl<T: Hash> Hash for LinkedStack<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
     }
-    ls.head = head;
 }
 
 // 独占借用递归 pop
@@ -225,6 +380,7 @@ fn pop_back_recursive<T>(link: &mut Link<T>) -> Option<T> {
 }
 
 // 子表 take 掉, 还个新的子表
+#[allow(dead_code)]
 fn pop_back_replace<T>(link: Link<T>) -> (Link<T>, Option<T>) {
     match link {
         None => (None, None),
@@ -239,7 +395,7 @@ fn pop_back_replace<T>(link: Link<T>) -> (Link<T>, Option<T>) {
     }
 }
 
-impl<T: std::fmt::Debug> std::fmt::Debug for LinkedStack<T> {
+impl<T: core::fmt::Debug> core::fmt::Debug for LinkedStack<T> {
     /// # Examples
     /// ```
     /// use too_many_linked_list::linked_stack::*;
@@ -250,7 +406,7 @@ impl<T: std::fmt::Debug> std::fmt::Debug for LinkedStack<T> {
     /// s.push(1);
     /// assert_eq!(format!("{:?}", &s), format!("{:?}", [1,3]), "notice the sequence of elements");
     /// ```
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_list().entries(self.iter()).finish()
     }
 }
@@ -265,16 +421,18 @@ impl<T> LinkedStack<T> {
     }
 
     pub fn push_verbose(&mut self, t: T) {
-        let head = std::mem::replace(&mut self.head, None);
+        let head = self.head.take();
         let new_node = Box::new(Node { elem: t, next: head });
         self.head = Some(new_node);
+        self.len += 1;
     }
 
     pub fn pop_verbose(&mut self) -> Option<T> {
-        let head = std::mem::replace(&mut self.head, None);
+        let head = self.head.take();
         match head {
             Some(node) => {
                 self.head = node.next;
+                self.len -= 1;
                 Some(node.elem)
             }
             None => None,
@@ -282,10 +440,15 @@ impl<T> LinkedStack<T> {
     }
 
     pub fn pop_back_verbose(&mut self) -> Option<T> {
-        pop_back_recursive_verbose(&mut self.head)
+        let ret = pop_back_recursive_verbose(&mut self.head);
+        if ret.is_some() {
+            self.len -= 1;
+        }
+        ret
     }
 }
 
+#[allow(clippy::manual_map)]
 fn pop_back_recursive_verbose<T>(link: &mut Link<T>) -> Option<T> {
     // 这个 match 对应 link.as_mut().and_then(|sub_node| {
     match link {
@@ -0,0 +1,40 @@
+//! 序列化为从栈顶到栈底的序列
+use super::*;
+use core::fmt;
+use core::marker::PhantomData;
+use ::serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+
+impl<T: Serialize> Serialize for LinkedStack<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LinkedStack<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StackVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for StackVisitor<T> {
+            type Value = LinkedStack<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut stack = LinkedStack::new();
+                // 记住队尾的 next, 按顺序接在后面, 不用 push_back 每次从头找, 也不用先收集再反转
+                let mut tail = &mut stack.head;
+                while let Some(elem) = seq.next_element()? {
+                    let node = tail.insert(Box::new(Node { elem, next: None }));
+                    tail = &mut node.next;
+                    stack.len += 1;
+                }
+                Ok(stack)
+            }
+        }
+
+        deserializer.deserialize_seq(StackVisitor(PhantomData))
+    }
+}
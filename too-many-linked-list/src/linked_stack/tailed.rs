@@ -0,0 +1,342 @@
+//! 带队尾指针的 `LinkedStack`, 两端的 push pop peek 都是 O(1)
+//!
+//! 只记住队尾还不够: `pop_back` 要找到倒数第二个节点, 所以每个节点还要指回前一个;
+//! 节点全部由裸指针串起来, 不混用 `Box` 的所有权, 免得 `Box` 移动时让指向节点的裸指针失效.
+//! 所有指针都是 `NonNull`, 对 `T` 协变, 与 `LinkedStack` 一样
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use alloc::boxed::Box;
+use super::LinkedStack;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+/// 如下函数编译成功证明了 `TailedStack<T>` 对 `T` 协变
+/// ```no_run
+/// # use too_many_linked_list::linked_stack::TailedStack;
+/// fn ensure_covariant<'long: 'short, 'short>(list_long: TailedStack<&'long i32>, mut list_short: TailedStack<&'short i32>) {
+///     let list_short_new: TailedStack<&'short i32> = list_long; // 证明协变
+/// }
+/// ```
+///
+/// # Examples
+/// ```
+/// use too_many_linked_list::linked_stack::TailedStack;
+/// let mut s = TailedStack::new();
+/// s.push(2);
+/// s.push(1);
+/// s.push_back(3);
+/// assert_eq!(s.peek_back(), Some(&3));
+/// assert_eq!(s.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+/// assert_eq!(s.pop_back(), Some(3));
+/// assert_eq!(s.pop(), Some(1));
+/// ```
+pub struct TailedStack<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _boo: PhantomData<T>,
+}
+
+impl<T> TailedStack<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        unsafe { self.head.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        unsafe { self.tail.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let new = Self::new_node(elem, self.head, None);
+        match self.head {
+            Some(old) => unsafe { (*old.as_ptr()).prev = Some(new) },
+            None => self.tail = Some(new),
+        }
+        self.head = Some(new);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let new = Self::new_node(elem, None, self.tail);
+        match self.tail {
+            Some(old) => unsafe { (*old.as_ptr()).next = Some(new) },
+            None => self.head = Some(new),
+        }
+        self.tail = Some(new);
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.head.map(|node| unsafe {
+            let node = Box::from_raw(node.as_ptr());
+            self.head = node.next;
+            match self.head {
+                Some(new) => (*new.as_ptr()).prev = None,
+                None => self.tail = None,
+            }
+            self.len -= 1;
+            node.elem
+        })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|node| unsafe {
+            let node = Box::from_raw(node.as_ptr());
+            self.tail = node.prev;
+            match self.tail {
+                Some(new) => (*new.as_ptr()).next = None,
+                None => self.head = None,
+            }
+            self.len -= 1;
+            node.elem
+        })
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    fn new_node(elem: T, next: Link<T>, prev: Link<T>) -> NonNull<Node<T>> {
+        let node = Box::into_raw(Box::new(Node { elem, next, prev }));
+        // SAFETY: Box::into_raw 不会返回空指针
+        unsafe { NonNull::new_unchecked(node) }
+    }
+}
+
+impl<T> Drop for TailedStack<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for TailedStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for TailedStack<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// 从 `LinkedStack` 转换, 顺序不变, O(n)
+impl<T> From<LinkedStack<T>> for TailedStack<T> {
+    fn from(mut stack: LinkedStack<T>) -> Self {
+        let mut tailed = Self::new();
+        while let Some(elem) = stack.pop() {
+            tailed.push_back(elem);
+        }
+        tailed
+    }
+}
+
+/// 转换回 `LinkedStack`, 顺序不变, O(n)
+impl<T> From<TailedStack<T>> for LinkedStack<T> {
+    fn from(mut tailed: TailedStack<T>) -> Self {
+        let mut stack = LinkedStack::new();
+        while let Some(elem) = tailed.pop_back() {
+            stack.push(elem);
+        }
+        stack
+    }
+}
+
+impl<T> Extend<T> for TailedStack<T> {
+    /// 依次 `push_back`
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for TailedStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tailed = Self::new();
+        tailed.extend(iter);
+        tailed
+    }
+}
+
+unsafe impl<T: Send> Send for TailedStack<T> {}
+unsafe impl<T: Sync> Sync for TailedStack<T> {}
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &(*node.as_ptr()).elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+pub struct IntoIter<T>(TailedStack<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for TailedStack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a TailedStack<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -5,6 +5,28 @@
 use super::*;
 
 type List<T> = LinkedStack<T>;
+
+/// 测试用, 绕过 `LinkedStack` 的循环 drop, 交给编译器生成的默认 drop:
+/// `Box<Node>` 的 drop 会递归地 drop `next`, 元素多时爆栈
+struct RecursiveDropStack<T>(List<T>);
+
+impl<T> RecursiveDropStack<T> {
+    fn new() -> Self {
+        Self(List::new())
+    }
+
+    fn push(&mut self, elem: T) {
+        self.0.push(elem)
+    }
+}
+
+impl<T> Drop for RecursiveDropStack<T> {
+    fn drop(&mut self) {
+        // 取走 head 后, 内层 `LinkedStack` 已经为空, 它的循环 drop 什么也不做
+        drop(self.0.head.take());
+    }
+}
+
 #[test]
 fn basics() {
     let mut list = List::new();
@@ -37,9 +59,9 @@ fn basics() {
 macro_rules! stack_info {
     () => ({
         let mut level = 0;
-        let mut sp = ptr::null_mut();
+        let mut sp = ptr::null_mut::<std::ffi::c_void>();
         backtrace::trace(|frame| {
-            if sp == ptr::null_mut() {
+            if sp.is_null() {
                 sp = frame.sp();
             }
             level += 1;
@@ -54,15 +76,9 @@ macro_rules! stack_info {
 #[ignore]
 fn recursion_drop_stackoverflow() {
     struct I(i32);
-    impl LinkedStackRecursionDrop for I {}
-    impl LinkedStackItem for I {
-        fn drop_recursive() -> bool {
-            true
-        }
-    }
 
     fn test(n: usize) {
-        let mut list = List::new();
+        let mut list = RecursiveDropStack::new();
         for i in 0..n {
             list.push(I(i as i32))
         }
@@ -74,7 +90,7 @@ fn recursion_drop_stackoverflow() {
     let mut n: usize = 512;
     loop {
         test(n);
-        n = n * 2;
+        n *= 2;
     }
 }
 
@@ -84,16 +100,10 @@ fn recursion_drop() {
     use std::ffi::c_void;
     use std::ptr;
 
-    thread_local!(static VEC_SP_DROP: RefCell<Vec<(i32, *mut c_void)>> = RefCell::new(Vec::new()));
+    thread_local!(static VEC_SP_DROP: RefCell<Vec<(i32, *mut c_void)>> = const { RefCell::new(Vec::new()) });
 
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
     struct I(i32);
-    impl LinkedStackRecursionDrop for I {}
-    impl LinkedStackItem for I {
-        fn drop_recursive() -> bool {
-            true
-        }
-    }
     
     // 使用递归 drop 打断点看 call stack 增加
     // 用 backtrace::trace 拿到 drop 时的 stack pointer , 应该不同, 栈增长, sp递减
@@ -108,7 +118,7 @@ fn recursion_drop() {
         }
     }
 
-    let mut list = List::new();
+    let mut list = RecursiveDropStack::new();
     list.push(I(1));
     list.push(I(2));
     list.push(I(3));
@@ -128,7 +138,7 @@ fn non_recursion_drop() {
     use std::ffi::c_void;
     use std::ptr;
 
-    thread_local!(static VEC_SP_DROP: RefCell<Vec<(i32, *mut c_void)>> = RefCell::new(Vec::new()));
+    thread_local!(static VEC_SP_DROP: RefCell<Vec<(i32, *mut c_void)>> = const { RefCell::new(Vec::new()) });
 
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
     struct I(i32);
@@ -168,9 +178,9 @@ fn peek() {
     assert_eq!(list.peek(), Some(&3));
     assert_eq!(list.peek_mut(), Some(&mut 3));
 
-    list.peek_mut().map(|value| {
+    if let Some(value) = list.peek_mut() {
         *value = 42
-    });
+    }
 
     assert_eq!(list.peek(), Some(&42));
     assert_eq!(list.pop(), Some(42));
@@ -179,17 +189,17 @@ fn peek() {
 #[test]
 fn test_reverse() {
     let mut list = List::new();
-    reverse(&mut list);
+    list.reverse();
     assert_eq!(list.iter().collect::<Vec<_>>(), [&0;0]);
 
     list.push(3);
-    reverse(&mut list);
+    list.reverse();
     assert_eq!(list.iter().collect::<Vec<_>>(), [&3]);
 
     list.push(2);
     list.push(1);
     assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3]);
-    reverse(&mut list);
+    list.reverse();
     assert_eq!(list.iter().collect::<Vec<_>>(), [&3, &2, &1]);
 }
 
@@ -358,4 +368,289 @@ fn iter_mut_split_at() {
     let mut list2 = iter.split_at();
     assert_eq!(list1.iter().collect::<Vec<_>>(), [&2]);
     assert_eq!(list2.iter().collect::<Vec<_>>(), [&1]);
-}
\ No newline at end of file
+}
+
+#[test]
+fn append_split_off_retain() {
+    let mut a: List<i32> = (1..=3).collect();
+    let mut b: List<i32> = (4..=6).collect();
+    assert_eq!(a.len(), 3);
+    a.append(&mut b);
+    assert_eq!(a.iter().collect::<Vec<_>>(), [&1, &2, &3, &4, &5, &6]);
+    assert_eq!((a.len(), b.len()), (6, 0));
+    b.append(&mut a);
+    assert_eq!((a.len(), b.len()), (0, 6));
+
+    let mut c = b.split_off(4);
+    assert_eq!(b.iter().collect::<Vec<_>>(), [&1, &2, &3, &4]);
+    assert_eq!(c.iter().collect::<Vec<_>>(), [&5, &6]);
+    assert_eq!(c.split_off(2).len(), 0);
+    assert_eq!(c.split_off(0).len(), 2);
+    assert!(c.is_empty());
+
+    b.retain(|x| x % 2 == 1);
+    assert_eq!(b.iter().collect::<Vec<_>>(), [&1, &3]);
+    assert_eq!(b.len(), 2);
+    assert!(b.contains(&3) && !b.contains(&2));
+    b.clear();
+    assert_eq!((b.len(), b.peek()), (0, None));
+
+    // iter_mut 的 split 与 insert 也要维护长度
+    let mut list: List<i32> = (1..=4).collect();
+    let mut iter = list.iter_mut_my();
+    iter.next();
+    iter.insert_at(10);
+    let rest = iter.split_at();
+    assert_eq!((list.len(), rest.len()), (1, 4));
+    let mut list = rest;
+    let mut iter = list.iter_mut_book();
+    iter.insert_after(20);
+    iter.next();
+    let rest = iter.split_after();
+    assert_eq!(list.iter().collect::<Vec<_>>(), [&10, &20]);
+    assert_eq!((list.len(), rest.len()), (2, 3));
+    list.pop_back();
+    list.pop_back_verbose();
+    assert_eq!(list.len(), 0);
+}
+
+#[test]
+fn clone_eq_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    fn hash<T: Hash>(x: &T) -> u64 {
+        let mut h = DefaultHasher::new();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    let mut a: List<i32> = (1..=3).collect();
+    let b = a.clone();
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+    a.push(0);
+    assert_ne!(a, b);
+    a.pop();
+    a.extend(vec![4, 5]);
+    assert_eq!(a.iter().collect::<Vec<_>>(), [&1, &2, &3, &4, &5]);
+    assert_ne!(a, b);
+    a.reverse();
+    assert_eq!(a, (1..=5).rev().collect());
+}
+
+#[test]
+fn long_stack_no_overflow() {
+    // 一百万个元素, 递归实现会爆栈
+    let n = 1_000_000;
+    let mut a: List<usize> = (0..n).collect();
+    let b = a.clone();
+    assert!(a == b);
+    a.reverse();
+    a.retain(|x| x % 2 == 0);
+    assert_eq!(a.len(), n / 2);
+    let tail = a.split_off(n / 4);
+    a.append(&mut b.clone());
+    assert_eq!(a.len(), n / 4 + n);
+    drop((a, b, tail));
+}
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use serde_test::{assert_ser_tokens, Token};
+    let mut s = LinkedStack::new();
+    s.push(3);
+    s.push(2);
+    s.push(1);
+    assert_ser_tokens(&s, &[
+        Token::Seq { len: None },
+        Token::I32(1),
+        Token::I32(2),
+        Token::I32(3),
+        Token::SeqEnd,
+    ]);
+
+    // 长栈反序列化是线性的
+    let json = serde_json::to_string(&(0..100_000).collect::<Vec<_>>()).unwrap();
+    let s: LinkedStack<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(s.peek(), Some(&0));
+    assert_eq!(s.iter().count(), 100_000);
+}
+
+#[test]
+fn tailed_basics() {
+    let mut list = TailedStack::new();
+    assert_eq!((list.pop(), list.pop_back()), (None, None));
+    assert_eq!((list.peek(), list.peek_back()), (None, None));
+
+    list.push(2);
+    list.push_back(3);
+    list.push(1);
+    list.push_back(4);
+    assert_eq!(list.len(), 4);
+    assert_eq!((list.peek(), list.peek_back()), (Some(&1), Some(&4)));
+    *list.peek_back_mut().unwrap() *= 10;
+    assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3, &40]);
+    assert_eq!(list.iter().rev().collect::<Vec<_>>(), [&40, &3, &2, &1]);
+
+    assert_eq!(list.pop_back(), Some(40));
+    assert_eq!(list.pop(), Some(1));
+    assert_eq!(list.pop_back(), Some(3));
+    // 只剩一个时两端是同一个节点
+    assert_eq!((list.peek(), list.peek_back()), (Some(&2), Some(&2)));
+    assert_eq!(list.pop_back(), Some(2));
+    assert!(list.is_empty());
+    assert_eq!((list.pop(), list.pop_back()), (None, None));
+
+    list.push_back(5);
+    assert_eq!(list.pop(), Some(5));
+    assert_eq!(list.peek_back(), None);
+}
+
+#[test]
+fn tailed_iter_and_convert() {
+    let mut list: TailedStack<i32> = (1..=5).collect();
+    for x in list.iter_mut() {
+        *x *= 2;
+    }
+    let mut iter = list.iter_mut();
+    assert_eq!(iter.next(), Some(&mut 2));
+    assert_eq!(iter.next_back(), Some(&mut 10));
+    assert_eq!(iter.len(), 3);
+
+    let stack: LinkedStack<i32> = list.into();
+    assert_eq!(stack.iter().collect::<Vec<_>>(), [&2, &4, &6, &8, &10]);
+    let list = TailedStack::from(stack);
+    let mut into_iter = list.into_iter();
+    assert_eq!(into_iter.next_back(), Some(10));
+    assert_eq!(into_iter.collect::<Vec<_>>(), [2, 4, 6, 8]);
+}
+
+#[test]
+fn tailed_drop() {
+    use std::rc::Rc;
+
+    let counter = Rc::new(());
+    let mut list = TailedStack::new();
+    for _ in 0..100_000 {
+        list.push_back(Rc::clone(&counter));
+    }
+    drop(list.pop_back());
+    assert_eq!(Rc::strong_count(&counter), 100_000);
+    // 循环释放, 长链表也不会爆栈
+    drop(list);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn atomic_basics() {
+    use std::rc::Rc;
+
+    let stack = AtomicStack::new();
+    assert_eq!((stack.pop(), stack.peek_with(|x: &i32| *x)), (None, None));
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.len_approx(), 3);
+    assert_eq!(stack.peek_with(|x| x * 10), Some(30));
+    assert_eq!(stack.pop(), Some(3));
+
+    // 节点原样交出, 不重新分配
+    let top = stack.peek_with(|x| x as *const i32).unwrap();
+    let taken = stack.take_all();
+    assert_eq!(taken.peek().unwrap() as *const i32, top);
+    assert_eq!(taken.iter().collect::<Vec<_>>(), [&2, &1]);
+    assert_eq!(taken.len(), 2);
+    assert!(stack.is_empty());
+    assert_eq!(stack.len_approx(), 0);
+    assert!(stack.take_all().is_empty());
+
+    let counter = Rc::new(());
+    let stack = AtomicStack::new();
+    for _ in 0..100_000 {
+        stack.push(Rc::clone(&counter));
+    }
+    drop(stack.pop());
+    assert_eq!(Rc::strong_count(&counter), 100_000);
+    // 循环释放, 长栈也不会爆栈
+    drop(stack);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn atomic_stress() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    const PRODUCERS: usize = 3;
+    const PER_PRODUCER: usize = 20_000;
+
+    let stack = AtomicStack::new();
+    let consumed = AtomicUsize::new(0);
+    let results: Vec<Vec<String>> = thread::scope(|s| {
+        for p in 0..PRODUCERS {
+            let stack = &stack;
+            s.spawn(move || {
+                for i in 0..PER_PRODUCER {
+                    stack.push(format!("{}-{}", p, i));
+                }
+            });
+        }
+        let consumers: Vec<_> = (0..2)
+            .map(|c| {
+                let (stack, consumed) = (&stack, &consumed);
+                s.spawn(move || {
+                    let mut got = Vec::new();
+                    while consumed.load(Ordering::Relaxed) < PRODUCERS * PER_PRODUCER {
+                        let before = got.len();
+                        if c == 0 && got.len() % 64 == 0 {
+                            got.extend(stack.take_all());
+                        } else if let Some(x) = stack.pop() {
+                            got.push(x);
+                        }
+                        consumed.fetch_add(got.len() - before, Ordering::Relaxed);
+                    }
+                    got
+                })
+            })
+            .collect();
+        consumers.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert!(stack.is_empty());
+
+    let mut all: Vec<String> = results.into_iter().flatten().collect();
+    all.sort();
+    all.dedup();
+    assert_eq!(all.len(), PRODUCERS * PER_PRODUCER, "elements lost or popped twice");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn atomic_peek_while_popping() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    const COUNT: usize = 20_000;
+
+    let stack = AtomicStack::new();
+    let done = AtomicBool::new(false);
+    thread::scope(|s| {
+        // 一边读栈顶, 一边有别的线程弹出并 drop 同一个元素
+        let (stack_ref, done_ref) = (&stack, &done);
+        s.spawn(move || {
+            while !done_ref.load(Ordering::Relaxed) {
+                stack_ref.peek_with(|x: &String| assert!(x.starts_with("elem")));
+            }
+        });
+        for i in 0..COUNT {
+            stack.push(format!("elem{}", i));
+            if i % 2 == 0 {
+                assert_eq!(stack.pop(), Some(format!("elem{}", i)));
+            }
+            if i % 1000 == 999 {
+                assert_eq!(stack.take_all().len(), 500);
+            }
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+    assert!(stack.is_empty());
+}
@@ -0,0 +1,182 @@
+//! 无锁多生产者多消费者队列 (Michael–Scott queue)
+//!
+//! 1. 布局与 `unsafe_queue::List` 相同, 从 `head` 出队, 在 `tail` 入队, 但两者都是原子指针
+//! 2. `head` 总是指向一个哨兵节点, 真正的队首是哨兵的下一个节点, 出队时它成为新的哨兵
+//! 3. `tail` 可能落后一个节点, 任何线程发现后都会帮忙把它推进
+//! 4. 出队摘下的旧哨兵交给 `hazard` 回收, 其他线程还在读它时不会被释放
+//!
+//! # Examples
+//! ```
+//! use std::thread;
+//! use too_many_linked_list::lockfree_queue::Queue;
+//!
+//! let queue = Queue::new();
+//! thread::scope(|s| {
+//!     for t in 0..4 {
+//!         let queue = &queue;
+//!         s.spawn(move || {
+//!             for i in 0..100 {
+//!                 queue.push(t * 100 + i);
+//!             }
+//!         });
+//!     }
+//! });
+//! assert_eq!(queue.len_approx(), 400);
+//! let mut all: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+//! all.sort();
+//! assert_eq!(all, (0..400).collect::<Vec<_>>());
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+
+use crate::hazard::{self, HazardPointer};
+
+#[cfg(test)]
+mod test;
+
+struct Node<T> {
+    // 哨兵节点的 `elem` 未初始化或已经被移走
+    elem: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn alloc(elem: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            elem,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+pub struct Queue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    // 入队后加, 出队后减, 并发时可能短暂为负
+    len: AtomicIsize,
+    _boo: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        let sentinel = Node::alloc(MaybeUninit::uninit());
+        Self {
+            head: AtomicPtr::new(sentinel),
+            tail: AtomicPtr::new(sentinel),
+            len: AtomicIsize::new(0),
+            _boo: PhantomData,
+        }
+    }
+
+    /// 入队到队尾
+    pub fn push(&self, elem: T) {
+        let new = Node::alloc(MaybeUninit::new(elem));
+        let mut hp = HazardPointer::new();
+        loop {
+            let tail = hp.protect(&self.tail);
+            // SAFETY: tail 已登记, 且 head 不会越过 tail, 所以它还没有被回收
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if tail != self.tail.load(Ordering::Acquire) {
+                continue;
+            }
+            if !next.is_null() {
+                // tail 落后了, 帮忙推进后重试
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            let linked = unsafe {
+                (*tail).next.compare_exchange(ptr::null_mut(), new, Ordering::Release, Ordering::Relaxed)
+            };
+            if linked.is_ok() {
+                // 失败说明别的线程已经帮忙推进了
+                let _ = self.tail.compare_exchange(tail, new, Ordering::Release, Ordering::Relaxed);
+                break;
+            }
+        }
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 从队首出队, 队列为空时返回 `None`
+    pub fn pop(&self) -> Option<T> {
+        let mut hp_head = HazardPointer::new();
+        let mut hp_next = HazardPointer::new();
+        loop {
+            let head = hp_head.protect(&self.head);
+            // SAFETY: head 已登记
+            let next = hp_next.protect(unsafe { &(*head).next });
+            // head 仍是队首, 说明登记 next 时它还没有出队, 也就没有被回收
+            if head != self.head.load(Ordering::Acquire) {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail {
+                // 不能让 head 越过 tail, 先帮忙推进 tail
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                // next 成为新的哨兵, 只有赢得 CAS 的线程会移走它的值
+                let elem = unsafe { ptr::read((*next).elem.as_ptr()) };
+                self.len.fetch_sub(1, Ordering::Relaxed);
+                hp_head.reset();
+                // SAFETY: 旧哨兵已经不能从队列中读到, 且只有这一个线程摘下了它
+                unsafe { hazard::retire(head) };
+                return Some(elem);
+            }
+        }
+    }
+
+    /// 并发修改时只是一个近似值
+    pub fn len_approx(&self) -> usize {
+        self.len.load(Ordering::Relaxed).max(0) as usize
+    }
+
+    /// 并发修改时只是一个瞬间的快照
+    pub fn is_empty(&self) -> bool {
+        let mut hp = HazardPointer::new();
+        let head = hp.protect(&self.head);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        // 独占访问, 直接释放; 已经 retire 的旧哨兵不在这条链上
+        let mut cur = *self.head.get_mut();
+        let mut sentinel = true;
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            if !sentinel {
+                unsafe { node.elem.assume_init_drop() };
+            }
+            sentinel = false;
+            cur = *node.next.get_mut();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").field("len_approx", &self.len_approx()).finish()
+    }
+}
@@ -0,0 +1,101 @@
+use super::Queue;
+use crate::hazard;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn test_basic() {
+    let queue = Queue::new();
+    assert!(queue.is_empty());
+    assert_eq!(queue.pop(), None);
+
+    queue.push(1);
+    queue.push(2);
+    queue.push(3);
+    assert_eq!(queue.len_approx(), 3);
+    assert_eq!(queue.pop(), Some(1));
+    assert_eq!(queue.pop(), Some(2));
+
+    queue.push(4);
+    assert_eq!(queue.pop(), Some(3));
+    assert_eq!(queue.pop(), Some(4));
+    assert_eq!(queue.pop(), None);
+    assert!(queue.is_empty());
+    assert_eq!(queue.len_approx(), 0);
+}
+
+#[test]
+fn test_drop() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    struct Counted;
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let queue = Queue::new();
+    for _ in 0..10 {
+        queue.push(Counted);
+    }
+    drop(queue.pop());
+    drop(queue.pop());
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    // 哨兵的值已经被移走, 不会重复 drop
+    drop(queue);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn test_mpmc_stress() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const PER_PRODUCER: usize = 20_000;
+
+    let queue = Queue::new();
+    let popped = AtomicUsize::new(0);
+    let results: Vec<Vec<(usize, usize)>> = thread::scope(|s| {
+        for p in 0..PRODUCERS {
+            let queue = &queue;
+            s.spawn(move || {
+                for i in 0..PER_PRODUCER {
+                    queue.push((p, i));
+                }
+            });
+        }
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let (queue, popped) = (&queue, &popped);
+                s.spawn(move || {
+                    let mut got = Vec::new();
+                    while popped.load(Ordering::Relaxed) < PRODUCERS * PER_PRODUCER {
+                        if let Some(x) = queue.pop() {
+                            popped.fetch_add(1, Ordering::Relaxed);
+                            got.push(x);
+                        }
+                    }
+                    got
+                })
+            })
+            .collect();
+        consumers.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert!(queue.is_empty());
+
+    // 每个消费者看到的同一生产者的元素必须有序
+    let mut seen = vec![vec![false; PER_PRODUCER]; PRODUCERS];
+    for got in &results {
+        let mut last = [None; PRODUCERS];
+        for &(p, i) in got {
+            assert!(last[p] < Some(i), "producer {} out of order", p);
+            last[p] = Some(i);
+            assert!(!seen[p][i], "({}, {}) popped twice", p, i);
+            seen[p][i] = true;
+        }
+    }
+    assert!(seen.iter().flatten().all(|&x| x));
+
+    // 待回收的节点不会无限堆积
+    hazard::collect();
+    assert!(hazard::pending() < 1000, "{} nodes never reclaimed", hazard::pending());
+}
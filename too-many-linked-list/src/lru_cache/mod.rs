@@ -0,0 +1,278 @@
+//! 基于 `unsafe_deque` 的 LRU 缓存
+//!
+//! 1. 链表按最近使用排序, 队首最新, 队尾最旧, 元素是 `(K, V)`
+//! 2. `HashMap` 从键映射到节点句柄 `NodeHandle`, 命中后 O(1) 把节点移到队首, 淘汰时 O(1) 弹出队尾
+//! 3. 节点地址固定, map 的键直接指向节点里的 `K`, 不需要 `K: Clone`
+//! 4. 链表开启了回收模式, 节点用到时才分配; 满了之后淘汰和插入复用同一块内存, 稳定后不再分配
+//! 5. 查找时和 `HashMap` 一样接受 `K: Borrow<Q>` 的 `&Q`, 比如 `String` 键可以用 `&str` 查
+//!
+//! # Examples
+//! ```
+//! use too_many_linked_list::lru_cache::LruCache;
+//! let mut cache = LruCache::new(2);
+//! cache.put("a", 1);
+//! cache.put("b", 2);
+//! assert_eq!(cache.get(&"a"), Some(&1)); // a 变为最新
+//! cache.put("c", 3); // 淘汰最旧的 b
+//! assert_eq!(cache.peek(&"b"), None);
+//! assert_eq!(cache.iter().collect::<Vec<_>>(), [(&"c", &3), (&"a", &1)]);
+//! ```
+
+use core::borrow::Borrow;
+use core::fmt::{self, Debug};
+use core::hash::{BuildHasher, Hash, Hasher};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+
+use crate::unsafe_deque::{self, LinkedList, NodeHandle};
+
+#[cfg(test)]
+mod test;
+
+/// 指向链表节点中的键, 节点释放前必须先从 map 中删掉
+struct KeyRef<K>(*const K);
+
+impl<K: Hash> Hash for KeyRef<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        unsafe { (*self.0).hash(state) }
+    }
+}
+
+impl<K: PartialEq> PartialEq for KeyRef<K> {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { *self.0 == *other.0 }
+    }
+}
+
+impl<K: Eq> Eq for KeyRef<K> {}
+
+/// 查找用的 `&Q`; 不能直接给 `KeyRef<K>` 实现 `Borrow<Q>`, 会和 `Borrow<T> for T` 冲突
+#[repr(transparent)]
+struct KeyWrapper<Q: ?Sized>(Q);
+
+impl<Q: ?Sized> KeyWrapper<Q> {
+    fn from_ref(key: &Q) -> &Self {
+        // SAFETY: repr(transparent), 布局相同
+        unsafe { &*(key as *const Q as *const Self) }
+    }
+}
+
+impl<Q: ?Sized + Hash> Hash for KeyWrapper<Q> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<Q: ?Sized + PartialEq> PartialEq for KeyWrapper<Q> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Q: ?Sized + Eq> Eq for KeyWrapper<Q> {}
+
+/// `K: Borrow<Q>` 保证 `K` 与 `Q` 的哈希和相等一致
+impl<K: Borrow<Q>, Q: ?Sized> Borrow<KeyWrapper<Q>> for KeyRef<K> {
+    fn borrow(&self) -> &KeyWrapper<Q> {
+        KeyWrapper::from_ref(unsafe { (*self.0).borrow() })
+    }
+}
+
+pub struct LruCache<K, V, S = RandomState> {
+    map: HashMap<KeyRef<K>, NodeHandle<(K, V)>, S>,
+    // 队首最新, 队尾最旧
+    list: LinkedList<(K, V)>,
+    cap: usize,
+}
+
+impl<K: Hash + Eq, V> LruCache<K, V> {
+    /// 最多保存 `cap` 个元素, `cap` 为 0 时什么都存不下
+    pub fn new(cap: usize) -> Self {
+        Self::with_hasher(cap, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> LruCache<K, V, S> {
+    /// 不预先分配, `cap` 很大也没关系
+    pub fn with_hasher(cap: usize, hash_builder: S) -> Self {
+        let mut list = LinkedList::new();
+        list.set_recycle(true);
+        Self {
+            map: HashMap::with_hasher(hash_builder),
+            list,
+            cap,
+        }
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    fn handle<Q>(&self, key: &Q) -> Option<NodeHandle<(K, V)>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(KeyWrapper::from_ref(key)).copied()
+    }
+
+    /// 查找并把它标记为最新
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    /// 查找并把它标记为最新
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let handle = self.handle(key)?;
+        // SAFETY: map 中的句柄都来自 self.list, 且节点仍在链表中
+        unsafe {
+            self.list.move_to_front(handle);
+            Some(&mut self.list.get_mut_by_handle(handle).1)
+        }
+    }
+
+    /// 只查找, 不改变新旧顺序
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let handle = self.handle(key)?;
+        unsafe { Some(&self.list.get_by_handle(handle).1) }
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(KeyWrapper::from_ref(key))
+    }
+
+    /// 最旧的元素, 不改变新旧顺序
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.list.back().map(|(k, v)| (k, v))
+    }
+
+    /// 插入或更新, 并标记为最新; 键已存在时返回旧值
+    /// 满了时淘汰最旧的元素
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(handle) = self.handle(&key) {
+            unsafe {
+                self.list.move_to_front(handle);
+                return Some(core::mem::replace(&mut self.list.get_mut_by_handle(handle).1, value));
+            }
+        }
+        if self.cap == 0 {
+            return None;
+        }
+        if self.len() == self.cap {
+            self.pop_lru();
+        }
+        let handle = self.list.push_front_handle((key, value));
+        // SAFETY: 刚插入的节点, 键的地址在节点释放前不会变
+        let key = unsafe { &self.list.get_by_handle(handle).0 };
+        self.map.insert(KeyRef(key), handle);
+        None
+    }
+
+    /// 删除并返回 `key` 对应的值
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let handle = self.map.remove(KeyWrapper::from_ref(key))?;
+        unsafe { Some(self.list.remove_by_handle(handle).1) }
+    }
+
+    /// 删除并返回最旧的元素
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, _) = self.list.back()?;
+        // 先删 map 中指向节点的键, 再释放节点
+        self.map.remove(&KeyRef(key));
+        self.list.pop_back()
+    }
+
+    /// 修改容量, 变小时从最旧的开始淘汰, 并释放淘汰出的节点; 变大时不分配
+    pub fn resize(&mut self, cap: usize) {
+        while self.len() > cap {
+            self.pop_lru();
+        }
+        if cap < self.cap {
+            self.list.shrink_to_fit();
+        }
+        self.cap = cap;
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list.clear();
+    }
+
+    /// 从最新到最旧
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.list.iter(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Debug, V: Debug, S: BuildHasher> Debug for LruCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// map 中的裸指针只指向自己链表中的节点
+unsafe impl<K: Send, V: Send, S: Send> Send for LruCache<K, V, S> {}
+unsafe impl<K: Sync, V: Sync, S: Sync> Sync for LruCache<K, V, S> {}
+
+pub struct Iter<'a, K, V> {
+    inner: unsafe_deque::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a LruCache<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,92 @@
+use super::LruCache;
+
+#[test]
+fn test_basic() {
+    let mut cache = LruCache::new(3);
+    assert!(cache.is_empty());
+    assert_eq!(cache.put(1, "one"), None);
+    assert_eq!(cache.put(2, "two"), None);
+    assert_eq!(cache.put(3, "three"), None);
+    assert_eq!(cache.len(), 3);
+
+    // get 会提升, peek 不会
+    assert_eq!(cache.get(&1), Some(&"one"));
+    assert_eq!(cache.peek(&2), Some(&"two"));
+    assert_eq!(cache.peek_lru(), Some((&2, &"two")));
+
+    assert_eq!(cache.put(4, "four"), None);
+    assert!(!cache.contains(&2));
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [4, 1, 3]);
+
+    // 更新已有的键返回旧值, 并提升
+    assert_eq!(cache.put(3, "THREE"), Some("three"));
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [3, 4, 1]);
+    *cache.get_mut(&1).unwrap() = "ONE";
+    assert_eq!(cache.pop_lru(), Some((4, "four")));
+    assert_eq!(cache.pop(&1), Some("ONE"));
+    assert_eq!(cache.pop(&1), None);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&2), None);
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.pop_lru(), None);
+}
+
+#[test]
+fn test_resize() {
+    let mut cache = LruCache::new(4);
+    for i in 0..4 {
+        cache.put(i, i * 10);
+    }
+    cache.resize(2);
+    assert_eq!(cache.cap(), 2);
+    assert_eq!(cache.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(), [2, 3]);
+    cache.resize(3);
+    cache.put(4, 40);
+    assert_eq!(cache.len(), 3);
+    cache.put(5, 50);
+    assert_eq!(format!("{:?}", cache), "{5: 50, 4: 40, 3: 30}");
+
+    let mut empty = LruCache::new(0);
+    assert_eq!(empty.put(1, 1), None);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_non_clone_keys() {
+    // 键不需要 Clone, 淘汰时正确 drop
+    let mut cache = LruCache::new(2);
+    for i in 0..10 {
+        cache.put(format!("key{}", i), Box::new(i));
+    }
+    assert_eq!(cache.peek("key9").map(|v| **v), Some(9));
+    assert_eq!(cache.pop_lru().map(|(k, v)| (k, *v)), Some(("key8".to_string(), 8)));
+}
+
+#[test]
+fn test_borrowed_lookup() {
+    // String 键用 &str 查, 不需要临时分配
+    let mut cache = LruCache::new(2);
+    cache.put(String::from("a"), 1);
+    cache.put(String::from("b"), 2);
+    assert_eq!(cache.get("a"), Some(&1));
+    *cache.get_mut("b").unwrap() += 10;
+    assert!(cache.contains("b"));
+    assert_eq!(cache.peek_lru(), Some((&String::from("a"), &1)));
+    assert_eq!(cache.pop("a"), Some(1));
+    assert_eq!(cache.peek("a"), None);
+    assert_eq!(cache.peek("b"), Some(&12));
+}
+
+#[test]
+fn test_lazy_allocation() {
+    // 容量很大也不会预先分配
+    let mut cache = LruCache::new(usize::MAX);
+    for i in 0..100 {
+        cache.put(i, i);
+    }
+    assert_eq!(cache.len(), 100);
+    cache.resize(usize::MAX / 2);
+    assert_eq!(cache.get(&0), Some(&0));
+}
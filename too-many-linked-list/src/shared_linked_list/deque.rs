@@ -0,0 +1,474 @@
+//! 实时双端队列, 两端各是一个由 `SharedLinkedList` 拼成的栈, 每个操作最坏 O(1)
+//!
+//! 1. 每一端的头部是该端最外侧的元素; 保持任何一端不超过另一端的 3 倍加 1,
+//!    一端为空时另一端至多一个元素, 两端的 peek pop 总能在某一端的头部找到
+//! 2. 失衡时重建: 长端只留外侧的 `keep` 个, 其余 `moving` 个反转后接到短端的内侧.
+//!    新链表不是一次建好的, 之后每个操作推进 `STEPS` 步, 在短端原有的元素弹完之前一定能完成 (证明见 `STEPS`);
+//!    期间两端照常 push pop, 新 push 的元素另起一段, 完成时放在新链表之前就行, 不必再复制
+//! 3. 与 `PersistentQueue` 一样没有均摊也没有惰性求值, 旧版本可以随意保留, 也可以交给别的线程
+//!
+//! 重建时要复制元素, 所以修改操作要求 `T: Clone`
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use super::SharedLinkedList;
+use super::queue::{Forward, Iter};
+
+const FRONT: usize = 0;
+const BACK: usize = 1;
+
+/// 重建期间每个操作推进的步数, 7 是对任何长度都成立的最小值.
+///
+/// 设开始重建时短端 `s` 个, 长端 `l` 个, 共 `n` 个. 此前两端平衡, 一次操作只让一端变化 1,
+/// 所以 `3s + 1 < l <= 3s + 4`, `n >= 4s + 2`. 重建要读长端 `l` 个, 读短端 `s` 个, 再接上至多
+/// `keep + s` 个, 其中 `keep = n / 2`, 共至多 `7s + 6` 步, 再加上发现已经做完的一步.
+/// 开始重建的操作连同之后的 `s` 个操作共推进 `7(s + 1)` 步, 所以之后至多 `s` 个操作就完成了:
+///
+/// 1. 这 `s` 个操作中的第 `i` 个之前, 短端至多被弹走 `i - 1` 个, 还剩至少一个,
+///    所以重建期间两端都不会被弹空, `pop` 不会转去另一端
+/// 2. 完成时两端本来是 `n / 2` 与 `n - n / 2`, 较少的一端至少 `2s + 1` 个; 期间的 `k <= s` 个操作
+///    至多让一端多 `a` 个, 另一端少 `b` 个, `a + b <= k`, 而 `a + 3b <= 3s <= 2(2s + 1)` 保证仍然平衡,
+///    所以下一次重建开始时上面的前提依然成立
+const STEPS: usize = 7;
+
+/// # Examples
+/// ```
+/// use too_many_linked_list::shared_linked_list::PersistentDeque;
+/// let deque: PersistentDeque<_> = (1..=3).collect();
+/// let wider = deque.push_front(0).push_back(4);
+/// assert_eq!(wider.iter().collect::<Vec<_>>(), [&0, &1, &2, &3, &4]);
+/// let (last, shorter) = wider.pop_back().unwrap();
+/// assert_eq!(*last, 4);
+/// assert_eq!(shorter.back(), Some(&3));
+/// // 旧版本不受影响
+/// assert_eq!(deque.len(), 3);
+/// assert_eq!(deque.front(), Some(&1));
+/// ```
+pub struct PersistentDeque<T> {
+    sides: [Side<T>; 2],
+    rebuild: Option<Rebuild<T>>,
+    // 重建期间搬动中的元素不在任何一端, 所以单独记录
+    len: usize,
+}
+
+/// 一端的元素: 各段首尾相接, 每段都不为空; 只有前 `len` 个有效, 之后的已经搬到另一端去了
+struct Side<T> {
+    pieces: SharedLinkedList<SharedLinkedList<T>>,
+    len: usize,
+}
+
+impl<T> Side<T> {
+    fn new() -> Self {
+        Self { pieces: SharedLinkedList::new(), len: 0 }
+    }
+
+    fn peek(&self) -> Option<&T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.pieces.head().and_then(SharedLinkedList::head)
+    }
+
+    /// `new_piece` 时另起一段, 否则加在第一段的头部
+    fn push(&self, elem: T, new_piece: bool) -> Self {
+        let pieces = match self.pieces.uncons() {
+            Some((first, rest)) if !new_piece => rest.cons(first.cons(elem)),
+            _ => self.pieces.cons(SharedLinkedList::new().cons(elem)),
+        };
+        Self { pieces, len: self.len + 1 }
+    }
+
+    fn pop(&self) -> Option<(&T, Self)> {
+        if self.len == 0 {
+            return None;
+        }
+        let (first, rest) = self.pieces.uncons()?;
+        let (elem, first) = first.uncons()?;
+        let pieces = if self.len == 1 {
+            // 无效的部分也一起丢掉
+            SharedLinkedList::new()
+        } else if first.is_empty() {
+            rest
+        } else {
+            rest.cons(first)
+        };
+        Some((elem, Self { pieces, len: self.len - 1 }))
+    }
+
+    /// 由外向内跳过 `skip` 个后的 `len` 个
+    fn forward(&self, skip: usize, len: usize) -> Forward<'_, T> {
+        Forward::new(&self.pieces, skip, len)
+    }
+}
+
+/// 由外向内逐个读一端开始重建时的有效元素
+struct Walker<T> {
+    pieces: SharedLinkedList<SharedLinkedList<T>>,
+    cur: SharedLinkedList<T>,
+    left: usize,
+}
+
+impl<T: Clone> Walker<T> {
+    fn new(side: &Side<T>) -> Self {
+        Self {
+            pieces: side.pieces.clone(),
+            cur: SharedLinkedList::new(),
+            left: side.len,
+        }
+    }
+
+    fn next(&mut self) -> Option<T> {
+        if self.left == 0 {
+            return None;
+        }
+        while self.cur.is_empty() {
+            let (piece, rest) = self.pieces.uncons()?;
+            self.cur = piece.clone();
+            self.pieces = rest;
+        }
+        let (elem, rest) = self.cur.uncons()?;
+        let elem = elem.clone();
+        self.cur = rest;
+        self.left -= 1;
+        Some(elem)
+    }
+}
+
+/// 逐步复制一端外侧的 `total` 个元素: 先逐个压入 `acc` (顺序反了), 再逐个弹出接到 `built` 之前, 顺序复原.
+/// 期间这一端又被弹走了 `popped` 个, 它们是最先读到的, 在 `acc` 的底部, 所以只接上 `total - popped` 个
+struct Transfer<T> {
+    src: Walker<T>,
+    total: usize,
+    read: usize,
+    acc: SharedLinkedList<T>,
+    popped: usize,
+    appended: usize,
+    built: SharedLinkedList<T>,
+}
+
+impl<T: Clone> Transfer<T> {
+    fn new(side: &Side<T>, total: usize) -> Self {
+        Self {
+            src: Walker::new(side),
+            total,
+            read: 0,
+            acc: SharedLinkedList::new(),
+            popped: 0,
+            appended: 0,
+            built: SharedLinkedList::new(),
+        }
+    }
+
+    /// 读一个元素, 已经读够时返回 false
+    fn read(&mut self) -> bool {
+        if self.read == self.total {
+            return false;
+        }
+        let elem = self.src.next().expect("side has total elements");
+        self.acc = self.acc.cons(elem);
+        self.read += 1;
+        true
+    }
+
+    /// 接上一个元素, 已经接完时返回 false
+    fn append(&mut self) -> bool {
+        if self.appended + self.popped >= self.total {
+            return false;
+        }
+        let (elem, rest) = self.acc.uncons().expect("acc holds the valid elements");
+        self.built = self.built.cons(elem.clone());
+        self.acc = rest;
+        self.appended += 1;
+        true
+    }
+
+    /// 这一端弹出了一个开始重建前就有的元素
+    fn pop(&mut self) {
+        self.popped += 1;
+        // 有效的都已经接上了, 弹出的那个就在 `built` 的头部
+        if self.appended + self.popped > self.total {
+            self.built = self.built.tail();
+            self.appended -= 1;
+        }
+    }
+}
+
+/// 按顺序做完: 读长端要留下的, 把其余的反转到短端的 `built` 上, 读短端, 接上长端, 接上短端
+struct Rebuild<T> {
+    long: usize,
+    // 开始时的长端, 迭代时从中读搬动中的元素
+    snapshot: Side<T>,
+    keep: usize,
+    moving: usize,
+    transfer: [Transfer<T>; 2],
+    // 重建开始后每一端新 push 且还没有弹出的个数, 它们在该端的第一段
+    fresh: [usize; 2],
+}
+
+impl<T: Clone> Rebuild<T> {
+    /// 推进一步, 已经完成时返回 false
+    fn step(&mut self) -> bool {
+        let (long, short) = (self.long, 1 - self.long);
+        if self.transfer[long].read() {
+            return true;
+        }
+        if let Some(elem) = self.transfer[long].src.next() {
+            let built = &mut self.transfer[short].built;
+            *built = built.cons(elem);
+            return true;
+        }
+        self.transfer[short].read() || self.transfer[long].append() || self.transfer[short].append()
+    }
+
+    /// 用建好的链表替换两端, 新 push 的那一段留在最前面
+    fn finish(self, sides: &mut [Side<T>; 2]) {
+        for (d, transfer) in IntoIterator::into_iter(self.transfer).enumerate() {
+            let mut pieces = SharedLinkedList::new();
+            let len = self.fresh[d] + transfer.built.len();
+            if !transfer.built.is_empty() {
+                pieces = pieces.cons(transfer.built);
+            }
+            if self.fresh[d] > 0 {
+                pieces = pieces.cons(sides[d].pieces.head().expect("fresh piece").clone());
+            }
+            sides[d] = Side { pieces, len };
+        }
+    }
+}
+
+impl<T> PersistentDeque<T> {
+    pub fn new() -> Self {
+        Self {
+            sides: [Side::new(), Side::new()],
+            rebuild: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.peek(FRONT)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.peek(BACK)
+    }
+
+    /// 从队首到队尾; 队首一端边走边读, 队尾一端是反的, 要先收集起来
+    pub fn iter(&self) -> Iter<'_, T> {
+        let front = self.sides[FRONT].forward(0, self.sides[FRONT].len);
+        let mut moving_front = Forward::empty();
+        // 由外向内收集队尾一端, 搬动中的元素在更内侧
+        let mut back: Vec<_> = self.sides[BACK].forward(0, self.sides[BACK].len).collect();
+        if let Some(rebuild) = &self.rebuild {
+            let moving = rebuild.snapshot.forward(rebuild.keep, rebuild.moving);
+            if rebuild.long == FRONT {
+                moving_front = moving;
+            } else {
+                back.extend(moving);
+            }
+        }
+        back.reverse();
+        Iter::new(front.chain(moving_front), back, self.len)
+    }
+
+    /// 这一端为空时, 元素在另一端的头部
+    fn peek(&self, d: usize) -> Option<&T> {
+        self.sides[d].peek().or_else(|| self.sides[1 - d].peek())
+    }
+}
+
+impl<T: Clone> PersistentDeque<T> {
+    /// 最坏 O(1)
+    pub fn push_front(&self, elem: T) -> Self {
+        self.push(FRONT, elem)
+    }
+
+    /// 最坏 O(1)
+    pub fn push_back(&self, elem: T) -> Self {
+        self.push(BACK, elem)
+    }
+
+    /// 队首元素和弹出后的新版本, 最坏 O(1); 元素仍属于 `self`, 所以返回引用
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        self.pop(FRONT)
+    }
+
+    /// 队尾元素和弹出后的新版本, 最坏 O(1)
+    pub fn pop_back(&self) -> Option<(&T, Self)> {
+        self.pop(BACK)
+    }
+
+    fn push(&self, d: usize, elem: T) -> Self {
+        let mut deque = self.clone();
+        let new_piece = match &mut deque.rebuild {
+            Some(rebuild) => {
+                rebuild.fresh[d] += 1;
+                rebuild.fresh[d] == 1
+            }
+            None => false,
+        };
+        deque.sides[d] = deque.sides[d].push(elem, new_piece);
+        deque.len += 1;
+        deque.check()
+    }
+
+    fn pop(&self, d: usize) -> Option<(&T, Self)> {
+        let d = if self.sides[d].len > 0 {
+            d
+        } else {
+            // 重建期间两端都不会被弹空, 见 `STEPS`
+            assert!(self.rebuild.is_none(), "side emptied while rebuilding");
+            1 - d
+        };
+        let (elem, side) = self.sides[d].pop()?;
+        let mut deque = self.clone();
+        deque.sides[d] = side;
+        deque.len -= 1;
+        if let Some(rebuild) = &mut deque.rebuild {
+            if rebuild.fresh[d] > 0 {
+                rebuild.fresh[d] -= 1;
+            } else {
+                rebuild.transfer[d].pop();
+            }
+        }
+        Some((elem, deque.check()))
+    }
+
+    /// 失衡时开始重建, 重建中则推进
+    fn check(mut self) -> Self {
+        if self.rebuild.is_none() {
+            let (front, back) = (self.sides[FRONT].len, self.sides[BACK].len);
+            let long = if front > 3 * back + 1 {
+                FRONT
+            } else if back > 3 * front + 1 {
+                BACK
+            } else {
+                return self;
+            };
+            let short = 1 - long;
+            let keep = self.len / 2;
+            let mut transfer = [
+                Transfer::new(&self.sides[FRONT], keep),
+                Transfer::new(&self.sides[BACK], keep),
+            ];
+            transfer[short].total = self.sides[short].len;
+            self.rebuild = Some(Rebuild {
+                long,
+                snapshot: self.sides[long].clone(),
+                keep,
+                moving: self.sides[long].len - keep,
+                transfer,
+                fresh: [0, 0],
+            });
+            self.sides[long].len = keep;
+        }
+        let mut rebuild = self.rebuild.take().expect("rebuilding");
+        for _ in 0..STEPS {
+            if !rebuild.step() {
+                rebuild.finish(&mut self.sides);
+                return self.check();
+            }
+        }
+        self.rebuild = Some(rebuild);
+        self
+    }
+}
+
+/// 只复制几个头指针, O(1)
+impl<T> Clone for PersistentDeque<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sides: [self.sides[FRONT].clone(), self.sides[BACK].clone()],
+            rebuild: self.rebuild.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Clone for Side<T> {
+    fn clone(&self) -> Self {
+        Self { pieces: self.pieces.clone(), len: self.len }
+    }
+}
+
+impl<T> Clone for Walker<T> {
+    fn clone(&self) -> Self {
+        Self {
+            pieces: self.pieces.clone(),
+            cur: self.cur.clone(),
+            left: self.left,
+        }
+    }
+}
+
+impl<T> Clone for Transfer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            src: self.src.clone(),
+            total: self.total,
+            read: self.read,
+            acc: self.acc.clone(),
+            popped: self.popped,
+            appended: self.appended,
+            built: self.built.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Rebuild<T> {
+    fn clone(&self) -> Self {
+        Self {
+            long: self.long,
+            snapshot: self.snapshot.clone(),
+            keep: self.keep,
+            moving: self.moving,
+            transfer: [self.transfer[FRONT].clone(), self.transfer[BACK].clone()],
+            fresh: self.fresh,
+        }
+    }
+}
+
+impl<T> Default for PersistentDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 依次加到队尾
+impl<T: Clone> FromIterator<T> for PersistentDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), |deque, elem| deque.push_back(elem))
+    }
+}
+
+/// 内部结构不同的版本也可能相等, 只比较元素
+impl<T: PartialEq> PartialEq for PersistentDeque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentDeque<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentDeque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1,11 +1,24 @@
 //! [4. A Persistent Stack](http://rust-unofficial.github.io/too-many-lists/third-final.html)
 //! 用 Arc 替代 Rc
+//!
+//! 在它之上还有持久化的 `PersistentQueue` 和 `PersistentDeque`, 每个版本都可以保留, 也可以交给别的线程
 
-use std::sync::Arc;
-use std::fmt;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
 
 #[cfg(test)]
 mod test;
+#[cfg(feature = "serde")]
+mod serde;
+pub mod queue;
+pub mod deque;
+
+#[cfg(feature = "serde")]
+pub use self::serde::shared;
+pub use self::queue::PersistentQueue;
+pub use self::deque::PersistentDeque;
 
 pub struct SharedLinkedList<T> {
     head: Link<T>,
@@ -18,6 +31,15 @@ struct Node<T> {
     elem: T,
     next: Link<T>,
     //prev
+    // 从本节点到链表末尾的元素个数, 后缀共享时长度也跟着共享
+    len: usize,
+}
+
+impl<T> Node<T> {
+    fn new(elem: T, next: Link<T>) -> Self {
+        let len = next.as_ref().map_or(0, |next| next.len) + 1;
+        Self { elem, next, len }
+    }
 }
 
 impl<T> SharedLinkedList<T> {
@@ -25,21 +47,44 @@ impl<T> SharedLinkedList<T> {
         Self { head: None }
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter(self.head.as_ref())
     }
 
+    /// 同 `cons`, 元素加在头部; 名字沿用自原文
     pub fn append(&self, elem: T) -> SharedLinkedList<T> {
+        self.cons(elem)
+    }
+
+    /// 在头部加一个元素, 返回的新链表与 `self` 共享全部节点, O(1)
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::shared_linked_list::SharedLinkedList;
+    /// let list = SharedLinkedList::new().cons(2);
+    /// let longer = list.cons(1);
+    /// assert_eq!(longer.iter().collect::<Vec<_>>(), [&1, &2]);
+    /// assert!(longer.tail().ptr_eq(&list));
+    /// ```
+    pub fn cons(&self, elem: T) -> SharedLinkedList<T> {
         Self {
-            head: Some(
-                Arc::new(Node {
-                     elem,
-                     next: self.head.clone() 
-                })
-            )
+            head: Some(Arc::new(Node::new(elem, self.head.clone())))
         }
     }
 
+    /// 拆成头部元素与其余部分, 空链表返回 `None`
+    pub fn uncons(&self) -> Option<(&T, SharedLinkedList<T>)> {
+        self.head.as_ref().map(|node| (&node.elem, Self { head: node.next.clone() }))
+    }
+
+    /// O(1)
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
     pub fn tail(&self) -> SharedLinkedList<T> {
         Self {
             head: self.head.as_ref().and_then(|node| {
@@ -52,10 +97,159 @@ impl<T> SharedLinkedList<T> {
     pub fn head(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.elem )
     }
+
+    /// 是否从头开始就是同一批节点, 两个空链表也算
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// 跳过前 `n` 个元素, 与 `self` 共享剩下的节点, O(n)
+    pub fn drop(&self, n: usize) -> SharedLinkedList<T> {
+        let mut link = self.head.as_ref();
+        for _ in 0..n {
+            match link {
+                Some(node) => link = node.next.as_ref(),
+                None => break,
+            }
+        }
+        Self { head: link.cloned() }
+    }
+
+    /// 前 `n` 个元素组成的新链表; `n` 不小于长度时就是 `self`, 共享全部节点, 否则复制这 `n` 个元素
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::shared_linked_list::SharedLinkedList;
+    /// let list: SharedLinkedList<_> = (1..=5).collect();
+    /// assert_eq!(list.take(2).iter().collect::<Vec<_>>(), [&1, &2]);
+    /// assert_eq!(list.drop(3).iter().collect::<Vec<_>>(), [&4, &5]);
+    /// assert!(list.take(5).ptr_eq(&list));
+    /// assert!(list.drop(3).ptr_eq(&list.tail().tail().tail()));
+    /// ```
+    pub fn take(&self, n: usize) -> SharedLinkedList<T>
+    where
+        T: Clone,
+    {
+        if n >= self.len() {
+            return self.clone();
+        }
+        Self::build(self.iter().take(n).cloned().collect(), Self::new())
+    }
+
+    /// 反转后的新链表, 复制全部元素
+    pub fn reverse(&self) -> SharedLinkedList<T>
+    where
+        T: Clone,
+    {
+        self.iter().fold(Self::new(), |list, elem| list.cons(elem.clone()))
+    }
+
+    /// 对每个元素调用 `f`, 结果组成新链表, 顺序不变
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> SharedLinkedList<U> {
+        SharedLinkedList::build(self.iter().map(f).collect(), SharedLinkedList::new())
+    }
+
+    /// 保留 `f` 返回 true 的元素; 最后一个被去掉的元素之后的后缀原样共享, 只复制它之前保留下来的元素
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::shared_linked_list::SharedLinkedList;
+    /// let list: SharedLinkedList<_> = (1..=6).collect();
+    /// let small = list.filter(|x| *x != 2);
+    /// assert_eq!(small.iter().collect::<Vec<_>>(), [&1, &3, &4, &5, &6]);
+    /// assert!(small.tail().ptr_eq(&list.drop(2)));
+    /// ```
+    pub fn filter<F: FnMut(&T) -> bool>(&self, mut f: F) -> SharedLinkedList<T>
+    where
+        T: Clone,
+    {
+        let mut kept = Vec::new();
+        // 最后一个被去掉的元素之后的后缀, 以及此时已经保留的个数
+        let (mut suffix, mut copied) = (self.head.as_ref(), 0);
+        let mut link = self.head.as_ref();
+        while let Some(node) = link {
+            if f(&node.elem) {
+                kept.push(&node.elem);
+            } else {
+                suffix = node.next.as_ref();
+                copied = kept.len();
+            }
+            link = node.next.as_ref();
+        }
+        kept.truncate(copied);
+        Self::build(kept.into_iter().cloned().collect(), Self { head: suffix.cloned() })
+    }
+
+    /// `self` 后面接上 `other`; 复制 `self` 的元素, 共享 `other` 的全部节点, O(len)
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::shared_linked_list::SharedLinkedList;
+    /// let left: SharedLinkedList<_> = (1..=2).collect();
+    /// let right: SharedLinkedList<_> = (3..=4).collect();
+    /// let both = left.concat(&right);
+    /// assert_eq!(both.iter().collect::<Vec<_>>(), [&1, &2, &3, &4]);
+    /// assert!(both.drop(2).ptr_eq(&right));
+    /// ```
+    pub fn concat(&self, other: &Self) -> SharedLinkedList<T>
+    where
+        T: Clone,
+    {
+        Self::build(self.iter().cloned().collect(), other.clone())
+    }
+
+    /// 节点不可变, 只能从尾部开始建: `elems` 依次排在 `suffix` 之前
+    fn build(elems: Vec<T>, suffix: Self) -> Self {
+        elems.into_iter().rev().fold(suffix, |list, elem| list.cons(elem))
+    }
 }
 
+/// 只复制头指针, O(1)
+impl<T> Clone for SharedLinkedList<T> {
+    fn clone(&self) -> Self {
+        Self { head: self.head.clone() }
+    }
+}
+
+impl<T> Default for SharedLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 第一个元素在头部
+impl<T> FromIterator<T> for SharedLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::build(iter.into_iter().collect(), Self::new())
+    }
+}
+
+/// 逐个比较, 走到同一个节点时剩下的后缀必然相同, 直接返回
+impl<T: PartialEq> PartialEq for SharedLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let (mut a, mut b) = (self.head.as_ref(), other.head.as_ref());
+        while let (Some(x), Some(y)) = (a, b) {
+            if Arc::ptr_eq(x, y) {
+                return true;
+            }
+            if x.elem != y.elem {
+                return false;
+            }
+            a = x.next.as_ref();
+            b = y.next.as_ref();
+        }
+        true
+    }
+}
+
+impl<T: Eq> Eq for SharedLinkedList<T> {}
+
 impl<T> Drop for SharedLinkedList<T> {
-    default fn drop(&mut self) {
+    fn drop(&mut self) {
         let mut link = self.head.take();
         while let Some(boxed_node) = link {
             if let Ok(mut node) = Arc::try_unwrap(boxed_node) {
@@ -77,8 +271,15 @@ impl<'a, T> Iterator for Iter<'a, T> {
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
 }
 
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
 impl<'a, T> Iter<'a, T> {
     pub fn peek(&self) -> Option<&'a T> {
         self.0.map(|node| {
@@ -0,0 +1,359 @@
+//! Hood-Melville 实时队列, 由两个 `SharedLinkedList` 组成, 每个操作最坏 O(1)
+//!
+//! 1. 从 `front` 的头部出队, 入队加在 `rear` 的头部, 所以 `rear` 是反的
+//! 2. `rear` 比 `front` 长时开始旋转, 算出 `front ++ reverse(rear)` 作为新的 `front`;
+//!    旋转不是一次做完, 之后每个操作推进两步, 在 `front` 取完之前一定能完成, 期间入队的元素放进新的 `rear`
+//! 3. 没有均摊也没有惰性求值: 对旧版本重复同一个操作代价也一样, 所以保留旧版本 (撤销) 不会让复杂度退化;
+//!    节点建好后不再改变, 各版本可以放心地交给别的线程
+//!
+//! 旋转时要复制元素, 所以修改操作要求 `T: Clone`; 元素较大时可以存 `Arc<T>`
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::{Chain, FromIterator};
+use super::SharedLinkedList;
+
+/// # Examples
+/// ```
+/// use too_many_linked_list::shared_linked_list::PersistentQueue;
+/// let empty = PersistentQueue::new();
+/// let one = empty.push_back(1);
+/// let two = one.push_back(2);
+/// let (first, rest) = two.pop_front().unwrap();
+/// assert_eq!(*first, 1);
+/// assert_eq!(rest.iter().collect::<Vec<_>>(), [&2]);
+/// // 旧版本都不受影响
+/// assert_eq!(two.len(), 2);
+/// assert_eq!(one.front(), Some(&1));
+/// assert!(empty.is_empty());
+/// ```
+pub struct PersistentQueue<T> {
+    // 队首一侧的元素个数, 旋转中还包括正在被反转过来的 `rotating`
+    front_len: usize,
+    front: SharedLinkedList<T>,
+    rotation: Rotation<T>,
+    // 正在旋转的旧 `rear`, 只用于迭代
+    rotating: SharedLinkedList<T>,
+    rear: SharedLinkedList<T>,
+}
+
+enum Rotation<T> {
+    Idle,
+    /// 同时把 `front` 复制反转到 `front_rev`, 把 `rear` 反转到 `rear_rev`;
+    /// `ok` 是 `front_rev` 中还没有出队的个数, 出队的是最早复制的那些, 在 `front_rev` 的底部
+    Reversing {
+        ok: usize,
+        front: SharedLinkedList<T>,
+        front_rev: SharedLinkedList<T>,
+        rear: SharedLinkedList<T>,
+        rear_rev: SharedLinkedList<T>,
+    },
+    /// 把 `front_rev` 中有效的 `ok` 个元素逐个接到 `rear_rev` 之前
+    Appending {
+        ok: usize,
+        front_rev: SharedLinkedList<T>,
+        rear_rev: SharedLinkedList<T>,
+    },
+    /// 旋转完成, 只在一个操作内部出现
+    Done(SharedLinkedList<T>),
+}
+
+impl<T: Clone> Rotation<T> {
+    fn exec(self) -> Self {
+        match self {
+            Rotation::Reversing { ok, front, front_rev, rear, rear_rev } => {
+                // 开始旋转时 `rear` 比 `front` 多一个, 两边同步前进
+                let (y, rest) = rear.uncons().expect("rear is one longer than front");
+                let rear_rev = rear_rev.cons(y.clone());
+                match front.uncons() {
+                    Some((x, front)) => Rotation::Reversing {
+                        ok: ok + 1,
+                        front,
+                        front_rev: front_rev.cons(x.clone()),
+                        rear: rest,
+                        rear_rev,
+                    },
+                    None => Rotation::Appending { ok, front_rev, rear_rev },
+                }
+            }
+            Rotation::Appending { ok: 0, rear_rev, .. } => Rotation::Done(rear_rev),
+            Rotation::Appending { ok, front_rev, rear_rev } => {
+                let (x, rest) = front_rev.uncons().expect("front_rev holds ok elements");
+                Rotation::Appending {
+                    ok: ok - 1,
+                    front_rev: rest,
+                    rear_rev: rear_rev.cons(x.clone()),
+                }
+            }
+            state => state,
+        }
+    }
+
+    /// 队首出队了一个旋转开始前就在 `front` 中的元素
+    fn invalidate(self) -> Self {
+        match self {
+            Rotation::Reversing { ok, front, front_rev, rear, rear_rev } => Rotation::Reversing {
+                ok: ok - 1,
+                front,
+                front_rev,
+                rear,
+                rear_rev,
+            },
+            // 有效的都已经接上了, 出队的那个就在结果的头部
+            Rotation::Appending { ok: 0, rear_rev, .. } => Rotation::Done(rear_rev.tail()),
+            Rotation::Appending { ok, front_rev, rear_rev } => Rotation::Appending {
+                ok: ok - 1,
+                front_rev,
+                rear_rev,
+            },
+            state => state,
+        }
+    }
+}
+
+impl<T> PersistentQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            front_len: 0,
+            front: SharedLinkedList::new(),
+            rotation: Rotation::Idle,
+            rotating: SharedLinkedList::new(),
+            rear: SharedLinkedList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front_len + self.rear.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front_len == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.front.head()
+    }
+
+    /// 从队首到队尾; `front` 边走边读, 反着的 `rear` 和 `rotating` 要先收集起来
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut back: Vec<_> = self.rear.iter().chain(self.rotating.iter()).collect();
+        back.reverse();
+        Iter::new(Forward::list(&self.front).chain(Forward::empty()), back, self.len())
+    }
+}
+
+impl<T: Clone> PersistentQueue<T> {
+    /// 入队后的新版本, 最坏 O(1)
+    pub fn push_back(&self, elem: T) -> Self {
+        let mut queue = self.clone();
+        queue.rear = queue.rear.cons(elem);
+        queue.check()
+    }
+
+    /// 队首元素和出队后的新版本, 最坏 O(1); 元素仍属于 `self`, 所以返回引用
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        let (elem, front) = self.front.uncons()?;
+        let mut queue = self.clone();
+        queue.front = front;
+        queue.front_len -= 1;
+        queue.rotation = queue.rotation.invalidate();
+        Some((elem, queue.check()))
+    }
+
+    fn check(mut self) -> Self {
+        if self.rear.len() > self.front_len {
+            // 旋转在 `front` 取完之前完成, 此时 `rear` 至多和 `front_len` 一样长
+            assert!(matches!(self.rotation, Rotation::Idle), "rotation still running");
+            self.rotating = core::mem::take(&mut self.rear);
+            self.front_len += self.rotating.len();
+            self.rotation = Rotation::Reversing {
+                ok: 0,
+                front: self.front.clone(),
+                front_rev: SharedLinkedList::new(),
+                rear: self.rotating.clone(),
+                rear_rev: SharedLinkedList::new(),
+            };
+        }
+        match self.rotation.exec().exec() {
+            Rotation::Done(front) => {
+                self.front = front;
+                self.rotation = Rotation::Idle;
+                self.rotating = SharedLinkedList::new();
+            }
+            rotation => self.rotation = rotation,
+        }
+        self
+    }
+}
+
+/// 只复制几个头指针, O(1)
+impl<T> Clone for PersistentQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            front_len: self.front_len,
+            front: self.front.clone(),
+            rotation: self.rotation.clone(),
+            rotating: self.rotating.clone(),
+            rear: self.rear.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Rotation<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Rotation::Idle => Rotation::Idle,
+            Rotation::Reversing { ok, front, front_rev, rear, rear_rev } => Rotation::Reversing {
+                ok: *ok,
+                front: front.clone(),
+                front_rev: front_rev.clone(),
+                rear: rear.clone(),
+                rear_rev: rear_rev.clone(),
+            },
+            Rotation::Appending { ok, front_rev, rear_rev } => Rotation::Appending {
+                ok: *ok,
+                front_rev: front_rev.clone(),
+                rear_rev: rear_rev.clone(),
+            },
+            Rotation::Done(list) => Rotation::Done(list.clone()),
+        }
+    }
+}
+
+impl<T> Default for PersistentQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 依次入队
+impl<T: Clone> FromIterator<T> for PersistentQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), |queue, elem| queue.push_back(elem))
+    }
+}
+
+/// 内部结构不同的版本也可能相等, 只比较元素
+impl<T: PartialEq> PartialEq for PersistentQueue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentQueue<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentQueue<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// `PersistentQueue` 与 `PersistentDeque` 的迭代器
+///
+/// 正向的部分边走边读; 反着存的部分创建时已经按顺序收集好, `back[start..]` 是还没有读到的
+pub struct Iter<'a, T> {
+    front: Chain<Forward<'a, T>, Forward<'a, T>>,
+    back: Vec<&'a T>,
+    start: usize,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(super) fn new(front: Chain<Forward<'a, T>, Forward<'a, T>>, back: Vec<&'a T>, len: usize) -> Self {
+        Iter { front, back, start: 0, len }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        match self.front.next() {
+            Some(elem) => Some(elem),
+            None => {
+                self.start += 1;
+                Some(self.back[self.start - 1])
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        if self.back.len() == self.start {
+            // 从后往前读到了正向的部分, 剩下的也只能先收集起来
+            self.back = self.front.by_ref().collect();
+            self.start = 0;
+        }
+        self.back.pop()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// 正向读首尾相接的若干段链表, 跳过 `skip` 个后再读 `left` 个, 不分配
+pub(super) struct Forward<'a, T> {
+    pieces: super::Iter<'a, SharedLinkedList<T>>,
+    cur: super::Iter<'a, T>,
+    skip: usize,
+    left: usize,
+}
+
+impl<'a, T> Forward<'a, T> {
+    pub(super) fn new(pieces: &'a SharedLinkedList<SharedLinkedList<T>>, skip: usize, len: usize) -> Self {
+        Self { pieces: pieces.iter(), cur: super::Iter(None), skip, left: len }
+    }
+
+    fn list(list: &'a SharedLinkedList<T>) -> Self {
+        Self { pieces: super::Iter(None), cur: list.iter(), skip: 0, left: list.len() }
+    }
+
+    pub(super) fn empty() -> Self {
+        Self { pieces: super::Iter(None), cur: super::Iter(None), skip: 0, left: 0 }
+    }
+
+    fn advance(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(elem) = self.cur.next() {
+                return Some(elem);
+            }
+            self.cur = self.pieces.next()?.iter();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Forward<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        while self.skip > 0 {
+            self.skip -= 1;
+            self.advance()?;
+        }
+        self.left -= 1;
+        self.advance()
+    }
+}
@@ -0,0 +1,155 @@
+//! 默认序列化为从头到尾的序列, 共享的后缀会被各自完整地写一遍
+//!
+//! 需要保留共享关系时, 用 `shared` 模块一起序列化一组链表:
+//! 每个节点只写一次, 记录下一个节点的下标, 反序列化后共享的后缀仍是同一批节点
+use super::*;
+use core::marker::PhantomData;
+use alloc::vec::Vec;
+use ::serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+
+impl<T: Serialize> Serialize for SharedLinkedList<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SharedLinkedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ListVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+            type Value = SharedLinkedList<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                // 节点不可变, 只能从尾部开始建, 先收集起来
+                let mut elems = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(elem) = seq.next_element()? {
+                    elems.push(elem);
+                }
+                Ok(SharedLinkedList::build(elems, SharedLinkedList::new()))
+            }
+        }
+
+        deserializer.deserialize_seq(ListVisitor(PhantomData))
+    }
+}
+
+/// 保留共享后缀的格式, 用于 `#[serde(with = "...")]` 修饰的 `Vec<SharedLinkedList<T>>`
+///
+/// 格式为 `{ nodes: [(elem, next)], heads: [head] }`, `next` 和 `head` 都是 `nodes` 中的下标,
+/// 节点按从尾到头的顺序写出, `next` 总是指向前面已经写出的节点
+/// # Examples
+/// ```
+/// use too_many_linked_list::shared_linked_list::{self, SharedLinkedList};
+///
+/// #[derive(::serde::Serialize, serde::Deserialize)]
+/// struct Snapshot {
+///     #[serde(with = "shared_linked_list::shared")]
+///     lists: Vec<SharedLinkedList<i32>>,
+/// }
+///
+/// let tail = SharedLinkedList::new().append(1).append(2);
+/// let lists = vec![tail.append(3), tail.append(4)];
+/// let json = serde_json::to_string(&Snapshot { lists }).unwrap();
+/// assert_eq!(json, r#"{"lists":{"nodes":[[1,null],[2,0],[3,1],[4,1]],"heads":[2,3]}}"#);
+///
+/// let Snapshot { lists } = serde_json::from_str(&json).unwrap();
+/// assert!(lists[0].tail().ptr_eq(&lists[1].tail()));
+/// ```
+pub mod shared {
+    use super::*;
+    use ::serde::de::Error;
+    // no_std 下没有 HashMap, 节点地址可以排序, 用 BTreeMap 代替
+    use alloc::collections::BTreeMap;
+
+    #[derive(::serde::Serialize)]
+    struct SharedRef<'a, T> {
+        nodes: Vec<(&'a T, Option<usize>)>,
+        heads: Vec<Option<usize>>,
+    }
+
+    #[derive(::serde::Deserialize)]
+    struct SharedOwned<T> {
+        nodes: Vec<(T, Option<usize>)>,
+        heads: Vec<Option<usize>>,
+    }
+
+    pub fn serialize<T, S>(lists: &[SharedLinkedList<T>], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        let mut index: BTreeMap<*const Node<T>, usize> = BTreeMap::new();
+        let mut nodes = Vec::new();
+        let mut heads = Vec::with_capacity(lists.len());
+        let mut fresh = Vec::new();
+        for list in lists {
+            // 从头走到第一个已经写过的节点, 之后的后缀是共享的
+            let mut link = list.head.as_ref();
+            let mut shared = None;
+            while let Some(node) = link {
+                if let Some(&i) = index.get(&Arc::as_ptr(node)) {
+                    shared = Some(i);
+                    break;
+                }
+                fresh.push(node);
+                link = node.next.as_ref();
+            }
+            // 从尾到头编号, 保证 next 指向已经写出的节点
+            let mut next = shared;
+            for node in fresh.drain(..).rev() {
+                let i = nodes.len();
+                nodes.push((&node.elem, next));
+                index.insert(Arc::as_ptr(node), i);
+                next = Some(i);
+            }
+            heads.push(next);
+        }
+        SharedRef { nodes, heads }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<SharedLinkedList<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let repr = SharedOwned::<T>::deserialize(deserializer)?;
+        let mut built: Vec<Arc<Node<T>>> = Vec::with_capacity(repr.nodes.len());
+        let link = |built: &[Arc<Node<T>>], i: Option<usize>| match i {
+            None => Ok(None),
+            Some(i) => built
+                .get(i)
+                .map(|node| Some(node.clone()))
+                .ok_or_else(|| D::Error::custom(format_args!("node index {} out of order", i))),
+        };
+        let mut result = Ok(Vec::with_capacity(repr.heads.len()));
+        for (elem, next) in repr.nodes {
+            match link(&built, next) {
+                Ok(next) => built.push(Arc::new(Node::new(elem, next))),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if let Ok(lists) = result.as_mut() {
+            for head in repr.heads {
+                match link(&built, head) {
+                    Ok(head) => lists.push(SharedLinkedList { head }),
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+        }
+        // 从头往尾释放, 没有被引用的节点逐个释放, 不会沿着 next 递归 drop
+        while built.pop().is_some() {}
+        result
+    }
+}
@@ -30,7 +30,7 @@ fn iter() {
     assert_eq!(iter.next(), Some(&2));
     assert_eq!(iter.next(), Some(&1));
 
-    assert_eq!(format!("{:?}", &list), format!("{:?}", [3,2,1]))
+    assert_eq!(format!("{:?}", list), format!("{:?}", [3,2,1]))
 }
 
 #[test]
@@ -43,8 +43,8 @@ fn iter_advance() {
         }
         iter.next();
     };
-    assert_eq!(format!("{:?}", &list1), format!("{:?}", [3,2,1]));
-    assert_eq!(format!("{:?}", &list2), format!("{:?}", [2,1]));
+    assert_eq!(format!("{:?}", list1), format!("{:?}", [3,2,1]));
+    assert_eq!(format!("{:?}", list2), format!("{:?}", [2,1]));
 }
 
 
@@ -64,7 +64,255 @@ fn complex() {
     let list2 = list1.tail();
     let list3 = list2.append("X");
 
-    assert_eq!(format!("{:?}", &list1), format!("{:?}", ["A", "B", "C", "D"]));
-    assert_eq!(format!("{:?}", &list2), format!("{:?}", ["B", "C", "D"]));
-    assert_eq!(format!("{:?}", &list3), format!("{:?}", ["X", "B", "C", "D"]));
-}
\ No newline at end of file
+    assert_eq!(format!("{:?}", list1), format!("{:?}", ["A", "B", "C", "D"]));
+    assert_eq!(format!("{:?}", list2), format!("{:?}", ["B", "C", "D"]));
+    assert_eq!(format!("{:?}", list3), format!("{:?}", ["X", "B", "C", "D"]));
+}
+#[test]
+fn persistent_api() {
+    let list: SharedLinkedList<i32> = (1..=5).collect();
+    assert_eq!((list.len(), list.is_empty()), (5, false));
+    let (head, rest) = list.uncons().unwrap();
+    assert_eq!((*head, rest.len()), (1, 4));
+    assert!(rest.ptr_eq(&list.tail()));
+    assert!(SharedLinkedList::<i32>::new().uncons().is_none());
+
+    let longer = list.cons(0);
+    assert_eq!(longer.len(), 6);
+    assert_eq!(list.drop(2).len(), 3);
+    assert!(list.drop(9).is_empty());
+    assert_eq!(list.take(3).iter().collect::<Vec<_>>(), [&1, &2, &3]);
+    assert_eq!(list.take(0).len(), 0);
+    assert_eq!(list.reverse().iter().collect::<Vec<_>>(), [&5, &4, &3, &2, &1]);
+    assert_eq!(list.map(|x| x * 10).iter().collect::<Vec<_>>(), [&10, &20, &30, &40, &50]);
+
+    // 没有元素被去掉时整条链表都共享
+    assert!(list.filter(|_| true).ptr_eq(&list));
+    let odd = list.filter(|x| x % 2 == 1);
+    assert_eq!(odd.iter().collect::<Vec<_>>(), [&1, &3, &5]);
+    assert!(odd.drop(2).ptr_eq(&list.drop(4)));
+    assert!(list.filter(|_| false).is_empty());
+
+    let both = odd.concat(&list);
+    assert_eq!(both.len(), 8);
+    assert!(both.drop(3).ptr_eq(&list));
+    assert!(SharedLinkedList::new().concat(&list).ptr_eq(&list));
+
+    let mut iter = both.iter();
+    iter.next();
+    assert_eq!(iter.len(), 7);
+    assert_eq!(iter.fork().len(), 7);
+}
+
+#[test]
+fn eq_and_long_lists() {
+    let tail: SharedLinkedList<i32> = (0..1_000_000).collect();
+    let a = tail.cons(1);
+    let b = tail.cons(1);
+    // 走到共享的后缀就不再比较
+    assert!(a == b);
+    assert!(a != tail.cons(2));
+    assert!(a != tail);
+    assert!(a.clone().ptr_eq(&a));
+
+    let copy: SharedLinkedList<i32> = (0..1_000_000).collect();
+    assert!(copy == tail);
+    let mapped = copy.map(|x| x + 1);
+    assert_eq!(mapped.drop(999_999).iter().next(), Some(&1_000_000));
+    // 循环释放, 长链表也不会爆栈
+    drop((a, b, tail, copy, mapped));
+}
+
+#[test]
+fn persistent_queue() {
+    let mut versions = vec![PersistentQueue::new()];
+    for i in 0..1000 {
+        let queue = versions.last().unwrap().push_back(i);
+        versions.push(queue);
+    }
+    let full = versions.last().unwrap().clone();
+    assert_eq!(full.len(), 1000);
+    assert!(full.iter().copied().eq(0..1000));
+
+    // 从同一个版本反复出队 (撤销后重做), 结果都一样
+    let mut queue = full.clone();
+    for i in 0..500 {
+        let (elem, next) = queue.pop_front().unwrap();
+        assert_eq!(*elem, i);
+        assert_eq!(queue.pop_front().unwrap().1, next);
+        queue = next.push_back(1000 + i);
+    }
+    assert!(queue.iter().copied().eq(500..1500));
+    assert_eq!(queue.iter().next_back(), Some(&1499));
+    for (i, version) in versions.iter().enumerate() {
+        assert_eq!(version.len(), i);
+        assert!(version.iter().copied().eq(0..i as i32));
+        assert!(version.iter().rev().copied().eq((0..i as i32).rev()));
+    }
+    assert_eq!(full, (0..1000).collect());
+    assert_ne!(full, queue);
+    assert_eq!(format!("{:?}", versions[3]), "[0, 1, 2]");
+}
+
+#[test]
+fn persistent_deque() {
+    // 一直从一端进另一端出, 每次都要把元素搬到另一端
+    let mut deque = PersistentDeque::new();
+    for i in 0..10_000 {
+        deque = deque.push_front(i);
+        if i % 3 == 2 {
+            let (elem, rest) = deque.pop_back().unwrap();
+            assert_eq!(*elem, i / 3);
+            deque = rest;
+        }
+    }
+    assert_eq!(deque.len(), 10_000 - 3333);
+    assert!(deque.iter().rev().copied().eq(3333..10_000));
+
+    let old = deque.clone();
+    while let Some((_, rest)) = deque.pop_front() {
+        deque = rest;
+    }
+    assert!(deque.is_empty());
+    assert_eq!(deque.back(), None);
+    assert_eq!(old.front(), Some(&9999));
+    assert_eq!(old.back(), Some(&3333));
+    assert_eq!(old.len(), 10_000 - 3333);
+
+    let small: PersistentDeque<_> = (0..3).collect();
+    assert_eq!(small.pop_front().unwrap().1.pop_front().unwrap().1.front(), Some(&2));
+    assert_eq!(small.pop_back().unwrap().1.pop_back().unwrap().1.back(), Some(&0));
+    assert_eq!(format!("{:?}", small.push_front(-1)), "[-1, 0, 1, 2]");
+}
+
+#[test]
+fn persistent_deque_every_short_sequence() {
+    use std::collections::VecDeque;
+
+    // 从两端交替读, 正向读到的部分不收集, 反向读到正向的部分时才收集
+    fn check(deque: &PersistentDeque<u32>, expect: &VecDeque<u32>) {
+        assert_eq!(deque.len(), expect.len());
+        assert!(deque.iter().eq(expect));
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        let mut it = deque.iter();
+        for i in 0.. {
+            assert_eq!(it.len(), expect.len() - front.len() - back.len());
+            let elem = if i % 3 == 0 { it.next_back().map(|x| back.push(*x)) } else { it.next().map(|x| front.push(*x)) };
+            if elem.is_none() {
+                break;
+            }
+        }
+        front.extend(back.into_iter().rev());
+        assert!(front.iter().eq(expect));
+    }
+
+    // 所有长度不超过 depth 的操作序列, 重建期间的任何一步都不会把一端弹空
+    fn all_sequences(deque: &PersistentDeque<u32>, expect: &mut VecDeque<u32>, depth: u32) {
+        check(deque, expect);
+        if depth == 0 {
+            return;
+        }
+        let x = depth;
+        expect.push_front(x);
+        all_sequences(&deque.push_front(x), expect, depth - 1);
+        expect.pop_front();
+        expect.push_back(x);
+        all_sequences(&deque.push_back(x), expect, depth - 1);
+        expect.pop_back();
+        if let Some((elem, rest)) = deque.pop_front() {
+            let y = expect.pop_front().unwrap();
+            assert_eq!(*elem, y);
+            all_sequences(&rest, expect, depth - 1);
+            expect.push_front(y);
+        }
+        if let Some((elem, rest)) = deque.pop_back() {
+            let y = expect.pop_back().unwrap();
+            assert_eq!(*elem, y);
+            all_sequences(&rest, expect, depth - 1);
+            expect.push_back(y);
+        }
+    }
+
+    for back in 0..16 {
+        for front in 0..2 {
+            let mut deque = PersistentDeque::new();
+            let mut expect = VecDeque::new();
+            for i in 0..back {
+                deque = deque.push_back(i);
+                expect.push_back(i);
+            }
+            for i in 0..front {
+                deque = deque.push_front(100 + i);
+                expect.push_front(100 + i);
+            }
+            all_sequences(&deque, &mut expect, 5);
+        }
+    }
+}
+
+#[test]
+fn persistent_versions_across_threads() {
+    use std::thread;
+    let mut queue = PersistentQueue::new();
+    let mut snapshots = Vec::new();
+    for i in 0..2000 {
+        queue = queue.push_back(i);
+        if i % 3 == 0 {
+            queue = queue.pop_front().unwrap().1;
+        }
+        if i % 200 == 0 {
+            snapshots.push(queue.clone());
+        }
+    }
+    // 每个线程拿着一个旧版本继续出入队, 互不影响
+    thread::scope(|s| {
+        for snapshot in &snapshots {
+            let expected: Vec<i32> = snapshot.iter().copied().collect();
+            s.spawn(move || {
+                let mut queue = snapshot.clone();
+                for &x in &expected {
+                    let (elem, rest) = queue.pop_front().unwrap();
+                    assert_eq!(*elem, x);
+                    queue = rest.push_back(-x);
+                }
+                assert!(queue.iter().copied().eq(expected.iter().map(|x| -x)));
+            });
+        }
+    });
+    assert!(snapshots[0].is_empty());
+    assert_eq!(snapshots[1].len(), 134);
+    assert_eq!(snapshots[1].front(), Some(&67));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use serde_test::{assert_ser_tokens, Token};
+    let list = SharedLinkedList::new().append(1).append(2);
+    // 长度是缓存的, 序列化时会带上
+    assert_ser_tokens(&list, &[Token::Seq { len: Some(2) }, Token::I32(2), Token::I32(1), Token::SeqEnd]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_shared() {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    struct Lists(#[serde(with = "super::shared")] Vec<SharedLinkedList<String>>);
+
+    let base = SharedLinkedList::new().append("a".to_string()).append("b".to_string());
+    let left = base.append("c".to_string());
+    let right = base.append("d".to_string()).append("e".to_string());
+    let lists = Lists(vec![left, SharedLinkedList::new(), right, base.tail()]);
+    let json = serde_json::to_string(&lists).unwrap();
+    assert_eq!(json.matches("\"a\"").count(), 1, "shared nodes are written once");
+
+    let Lists(lists) = serde_json::from_str(&json).unwrap();
+    let contents: Vec<Vec<&str>> = lists.iter().map(|l| l.iter().map(|s| s.as_str()).collect()).collect();
+    assert_eq!(contents, [vec!["c", "b", "a"], vec![], vec!["e", "d", "b", "a"], vec!["a"]]);
+    assert!(lists[0].tail().ptr_eq(&lists[2].tail().tail()));
+    assert!(lists[3].ptr_eq(&lists[0].tail().tail()));
+    assert!(lists[1].ptr_eq(&SharedLinkedList::new()));
+
+    let err = serde_json::from_str::<Lists>(r#"{"nodes":[["a",1],["b",null]],"heads":[0]}"#);
+    assert!(err.is_err(), "next must point to an earlier node");
+}
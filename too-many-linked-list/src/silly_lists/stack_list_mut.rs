@@ -28,7 +28,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
-            self.next = node.prev.as_ref().map(|prev| &**prev);
+            self.next = node.prev.as_deref();
             &node.data
         })
     }
@@ -36,10 +36,10 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
 #[cfg(test)]
 mod test {
-    use super::List;
-
     // the data struct is invalid
     /*
+    use super::List;
+
     #[test]
     fn cell() {
         use std::cell::Cell;
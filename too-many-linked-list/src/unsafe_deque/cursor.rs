@@ -3,14 +3,23 @@
 //! [7.11 Testing Cursors](https://rust-unofficial.github.io/too-many-lists/sixth-cursors-testing.html)
 use super::*;
 
-pub struct CursorMut<'a, T> {
-    list: &'a mut LinkedList<T>,
+/// 只读游标, 可以同时存在多个
+pub struct Cursor<'a, T, A: Allocator = Global> {
+    list: &'a LinkedList<T, A>,
     cur: Link<T>,
     index: Option<usize>,
 }
 
-impl<'a, T> From<&'a mut LinkedList<T>> for CursorMut<'a, T> {
-    fn from(l: &'a mut LinkedList<T>) -> Self {
+impl<'a, T, A: Allocator> Clone for Cursor<'a, T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, A: Allocator> Copy for Cursor<'a, T, A> {}
+
+impl<'a, T, A: Allocator> From<&'a LinkedList<T, A>> for Cursor<'a, T, A> {
+    fn from(l: &'a LinkedList<T, A>) -> Self {
         Self {
             list: l,
             cur: None,
@@ -19,7 +28,7 @@ impl<'a, T> From<&'a mut LinkedList<T>> for CursorMut<'a, T> {
     }
 }
 
-impl<'a, T> CursorMut<'a, T> {
+impl<'a, T, A: Allocator> Cursor<'a, T, A> {
     pub fn index(&self) -> Option<usize> {
         self.index
     }
@@ -66,11 +75,12 @@ impl<'a, T> CursorMut<'a, T> {
         }
     }
 
-    pub fn current(&mut self) -> Option<&mut T> {
-        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    /// 返回的引用与 `list` 同生存期, 不受游标后续移动影响
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).elem) }
     }
 
-    pub fn peek_next(&mut self) -> Option<&mut T> {
+    pub fn peek_next(&self) -> Option<&'a T> {
         unsafe {
             let next = if let Some(cur) = self.cur {
                 // Normal case, try to follow the cur node's back pointer
@@ -81,11 +91,11 @@ impl<'a, T> CursorMut<'a, T> {
             };
 
             // Yield the element if the next node exists
-            next.map(|node| &mut (*node.as_ptr()).elem)
+            next.map(|node| &(*node.as_ptr()).elem)
         }
     }
 
-    pub fn peek_prev(&mut self) -> Option<&mut T> {
+    pub fn peek_prev(&self) -> Option<&'a T> {
         unsafe {
             let prev = if let Some(cur) = self.cur {
                 // Normal case, try to follow the cur node's front pointer
@@ -96,135 +106,228 @@ impl<'a, T> CursorMut<'a, T> {
             };
 
             // Yield the element if the prev node exists
-            prev.map(|node| &mut (*node.as_ptr()).elem)
+            prev.map(|node| &(*node.as_ptr()).elem)
         }
     }
+}
 
-    pub fn split_before(&mut self) -> LinkedList<T> {
-        // We have this:
-        //
-        //     list.front -> A <-> B <-> C <-> D <- list.back
-        //                               ^
-        //                              cur
-        //
-        //
-        // And we want to produce this:
-        //
-        //     list.front -> C <-> D <- list.back
-        //                   ^
-        //                  cur
-        //
-        //
-        //    return.front -> A <-> B <- return.back
-        //
-        if let Some(cur) = self.cur {
-            // We are pointing at a real element, so the list is non-empty.
-            unsafe {
-                // Current state
-                let old_len = self.list.len;
-                let old_idx = self.index.unwrap();
-                let prev = (*cur.as_ptr()).front;
+pub struct CursorMut<'a, T, A: Allocator = Global> {
+    list: &'a mut LinkedList<T, A>,
+    cur: Link<T>,
+    index: Option<usize>,
+}
 
-                // What self will become
-                let new_len = old_len - old_idx;
-                let new_front = self.cur;
-                let new_back = self.list.back;
-                let new_idx = Some(0);
+impl<'a, T, A: Allocator> From<&'a mut LinkedList<T, A>> for CursorMut<'a, T, A> {
+    fn from(l: &'a mut LinkedList<T, A>) -> Self {
+        Self {
+            list: l,
+            cur: None,
+            index: None,
+        }
+    }
+}
 
-                // What the output will become
-                let output_len = old_len - new_len;
-                let output_front = self.list.front;
-                let output_back = prev;
+impl<'a, T, A: Allocator> CursorMut<'a, T, A> {
+    /// 直接停在 `node` 上, `index` 必须是它在链表中的位置
+    pub(super) unsafe fn at_node(list: &'a mut LinkedList<T, A>, node: NonNull<Node<T>>, index: usize) -> Self {
+        Self {
+            list,
+            cur: Some(node),
+            index: Some(index),
+        }
+    }
 
-                // Break the links between cur and prev
-                if let Some(prev) = prev {
-                    (*cur.as_ptr()).front = None;
-                    (*prev.as_ptr()).back = None;
+    /// 当前节点及其下标, 在幽灵元素上时为 `None`, 之后可以用 `at_node` 回到这里
+    pub(super) fn position(&self) -> Option<(NonNull<Node<T>>, usize)> {
+        self.cur.zip(self.index)
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// 检查所在链表的不变量, 以及游标的节点确实在链表的第 `index` 个位置, O(n)
+    pub fn check_invariants(&self) {
+        self.list.check_invariants();
+        match (self.cur, self.index) {
+            (None, None) => {}
+            (Some(cur), Some(index)) => {
+                assert!(index < self.list.len, "cursor index {} out of bounds", index);
+                let mut node = self.list.front;
+                for _ in 0..index {
+                    node = node.and_then(|node| unsafe { (*node.as_ptr()).back });
                 }
+                assert_eq!(node, Some(cur), "cursor index does not match its node");
+            }
+            _ => panic!("cursor has only one of node and index"),
+        }
+    }
 
-                // Produce the result:
-                self.list.len = new_len;
-                self.list.front = new_front;
-                self.list.back = new_back;
-                self.index = new_idx;
+    #[inline(always)]
+    fn debug_check(&self) {
+        #[cfg(feature = "debug-invariants")]
+        self.check_invariants();
+    }
+
+    /// 以只读游标查看当前位置, 借用期间不能再通过 `CursorMut` 修改
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            list: self.list,
+            cur: self.cur,
+            index: self.index,
+        }
+    }
 
-                LinkedList {
-                    front: output_front,
-                    back: output_back,
-                    len: output_len,
-                    _boo: PhantomData,
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                // We're on a real element, go to its next (back)
+                self.cur = (*cur.as_ptr()).back;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    // We just walked to the ghost, no more index
+                    self.index = None;
                 }
             }
+        } else if !self.list.is_empty() {
+            // We're at the ghost, and there is a real front, so move to it!
+            self.cur = self.list.front;
+            self.index = Some(0)
         } else {
-            // We're at the ghost, just replace our list with an empty one.
-            // No other state needs to be changed.
-            std::mem::replace(self.list, LinkedList::new())
+            // We're at the ghost, but that's the only element... do nothing.
         }
+        self.debug_check();
     }
 
-    pub fn split_after(&mut self) -> LinkedList<T> {
-        // We have this:
-        //
-        //     list.front -> A <-> B <-> C <-> D <- list.back
-        //                         ^
-        //                        cur
-        //
-        //
-        // And we want to produce this:
-        //
-        //     list.front -> A <-> B <- list.back
-        //                         ^
-        //                        cur
-        //
-        //
-        //    return.front -> C <-> D <- return.back
-        //
+    pub fn move_prev(&mut self) {
         if let Some(cur) = self.cur {
-            // We are pointing at a real element, so the list is non-empty.
             unsafe {
-                // Current state
-                let old_len = self.list.len;
-                let old_idx = self.index.unwrap();
-                let next = (*cur.as_ptr()).back;
+                // We're on a real element, go to its previous (front)
+                self.cur = (*cur.as_ptr()).front;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    // We just walked to the ghost, no more index
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            // We're at the ghost, and there is a real back, so move to it!
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1)
+        } else {
+            // We're at the ghost, but that's the only element... do nothing.
+        }
+        self.debug_check();
+    }
 
-                // What self will become
-                let new_len = old_idx + 1;
-                let new_back = self.cur;
-                let new_front = self.list.front;
-                let new_idx = Some(old_idx);
+    /// 移到下标 `index` 上, `index == len` 时移到幽灵元素上;
+    /// 从队首, 队尾, 当前位置三者中最近的一处开始走
+    /// # Panics
+    /// `index > len` 时 panic
+    pub fn move_to(&mut self, index: usize) {
+        let len = self.list.len;
+        assert!(index <= len, "cursor index {} out of bounds for length {}", index, len);
+        if index == len {
+            self.cur = None;
+            self.index = None;
+            self.debug_check();
+            return;
+        }
+        // (起点, 起点下标), 候选中步数最少的
+        let mut start = (self.list.front, 0);
+        if len - 1 - index < index {
+            start = (self.list.back, len - 1);
+        }
+        if let (Some(_), Some(i)) = (self.cur, self.index) {
+            if i.abs_diff(index) < start.1.abs_diff(index) {
+                start = (self.cur, i);
+            }
+        }
+        let (mut node, mut i) = start;
+        unsafe {
+            while i < index {
+                node = (*node.unwrap_unchecked().as_ptr()).back;
+                i += 1;
+            }
+            while i > index {
+                node = (*node.unwrap_unchecked().as_ptr()).front;
+                i -= 1;
+            }
+        }
+        self.cur = node;
+        self.index = Some(index);
+        self.debug_check();
+    }
 
-                // What the output will become
-                let output_len = old_len - new_len;
-                let output_front = next;
-                let output_back = self.list.back;
+    /// 相对移动 `offset` 步, 正数向后负数向前, 与连续调用 `move_next`/`move_prev` 等价,
+    /// 即把幽灵元素看作下标 `len`, 在 `len + 1` 个位置上循环
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list: LinkedList<_> = (0..5).collect();
+    /// let mut cursor = list.cursor_at(1);
+    /// cursor.seek(2);
+    /// assert_eq!(cursor.current(), Some(&mut 3));
+    /// cursor.seek(-4); // 3 -> 2 -> 1 -> 0 -> 幽灵元素
+    /// assert_eq!(cursor.index(), None);
+    /// cursor.seek(-1);
+    /// assert_eq!(cursor.index(), Some(4));
+    /// ```
+    pub fn seek(&mut self, offset: isize) {
+        let positions = self.list.len + 1;
+        let pos = self.index.unwrap_or(self.list.len);
+        let step = offset.unsigned_abs() % positions;
+        let target = if offset >= 0 {
+            (pos + step) % positions
+        } else {
+            (pos + positions - step) % positions
+        };
+        self.move_to(target);
+    }
 
-                // Break the links between cur and next
-                if let Some(next) = next {
-                    (*cur.as_ptr()).back = None;
-                    (*next.as_ptr()).front = None;
-                }
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
 
-                // Produce the result:
-                self.list.len = new_len;
-                self.list.front = new_front;
-                self.list.back = new_back;
-                self.index = new_idx;
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = if let Some(cur) = self.cur {
+                // Normal case, try to follow the cur node's back pointer
+                (*cur.as_ptr()).back
+            } else {
+                // Ghost case, try to use the list's front pointer
+                self.list.front
+            };
 
-                LinkedList {
-                    front: output_front,
-                    back: output_back,
-                    len: output_len,
-                    _boo: PhantomData,
-                }
-            }
-        } else {
-            // We're at the ghost, just replace our list with an empty one.
-            // No other state needs to be changed.
-            std::mem::replace(self.list, LinkedList::new())
+            // Yield the element if the next node exists
+            next.map(|node| &mut (*node.as_ptr()).elem)
         }
     }
 
-    pub fn splice_before(&mut self, mut input: LinkedList<T>) {
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = if let Some(cur) = self.cur {
+                // Normal case, try to follow the cur node's front pointer
+                (*cur.as_ptr()).front
+            } else {
+                // Ghost case, try to use the list's back pointer
+                self.list.back
+            };
+
+            // Yield the element if the prev node exists
+            prev.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn splice_before(&mut self, mut input: LinkedList<T, A>) {
+        self.splice_before_from(&mut input);
+        // Input dropped here
+    }
+
+    /// 同 `splice_before`, 但只拿走 `input` 的节点, `input` 本身留下变为空
+    pub(super) fn splice_before_from(&mut self, input: &mut LinkedList<T, A>) {
         // We have this:
         //
         // input.front -> 1 <-> 2 <- input.back
@@ -275,18 +378,21 @@ impl<'a, T> CursorMut<'a, T> {
                 self.list.back = Some(in_back);
             } else {
                 // We're empty, become the input, remain on the ghost
-                std::mem::swap(self.list, &mut input);
+                // 只拿走节点, 分配器仍用自己的
+                self.list.front = input.front.take();
+                self.list.back = input.back.take();
             }
 
             self.list.len += input.len;
             // Not necessary but Polite To Do
             input.len = 0;
-
-            // Input dropped here
+            input.handles.move_all(&mut self.list.handles);
         }
+        self.debug_check();
+        input.debug_check();
     }
 
-    pub fn splice_after(&mut self, mut input: LinkedList<T>) {
+    pub fn splice_after(&mut self, mut input: LinkedList<T, A>) {
         // We have this:
         //
         // input.front -> 1 <-> 2 <- input.back
@@ -336,14 +442,212 @@ impl<'a, T> CursorMut<'a, T> {
                 self.list.front = Some(in_front);
             } else {
                 // We're empty, become the input, remain on the ghost
-                std::mem::swap(self.list, &mut input);
+                // 只拿走节点, 分配器仍用自己的
+                self.list.front = input.front.take();
+                self.list.back = input.back.take();
             }
 
             self.list.len += input.len;
             // Not necessary but Polite To Do
             input.len = 0;
+            input.handles.move_all(&mut self.list.handles);
 
             // Input dropped here
         }
+        self.debug_check();
+    }
+
+    /// 在当前元素之前插入, 在幽灵元素上时插入到队尾
+    pub fn insert_before(&mut self, elem: T) {
+        unsafe {
+            let new = self.list.new_node(elem);
+            self.list.link_node_before(new, self.cur);
+        }
+        // 插入到当前元素之前, 当前元素后移一位; 在幽灵元素上时仍没有 index
+        if let Some(index) = self.index.as_mut() {
+            *index += 1;
+        }
+        self.debug_check();
+    }
+
+    /// 在当前元素之后插入, 在幽灵元素上时插入到队首
+    pub fn insert_after(&mut self, elem: T) {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).back,
+                None => self.list.front,
+            };
+            let new = self.list.new_node(elem);
+            self.list.link_node_before(new, next);
+        }
+        // Index doesn't change
+        self.debug_check();
+    }
+
+    /// 删除当前元素并返回其值, 游标移到下一个元素, 在幽灵元素上时返回 `None`
+    pub fn remove_current(&mut self) -> Option<T> {
+        let elem = self.unlink_current()
+            .map(|node| unsafe { self.list.free_node(node) });
+        self.debug_check();
+        elem
+    }
+
+    /// 替换当前元素, 返回旧值; 在幽灵元素上时什么都不做, 原样返回 `Err(elem)`
+    pub fn replace_current(&mut self, elem: T) -> Result<T, T> {
+        match self.current() {
+            Some(cur) => Ok(core::mem::replace(cur, elem)),
+            None => Err(elem),
+        }
+    }
+
+    /// 摘下当前节点, 游标移到下一个节点, index 不变, 若下一个是幽灵元素则没有 index
+    fn unlink_current(&mut self) -> Link<T> {
+        let cur = self.cur?;
+        unsafe {
+            self.cur = (*cur.as_ptr()).back;
+            self.list.unlink_node(cur);
+        }
+        if self.cur.is_none() {
+            self.index = None;
+        }
+        Some(cur)
+    }
+}
+
+/// 需要产生新链表的操作, 新链表使用 clone 出来的分配器
+impl<'a, T, A: Allocator + Clone> CursorMut<'a, T, A> {
+    pub fn split_before(&mut self) -> LinkedList<T, A> {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                               ^
+        //                              cur
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> C <-> D <- list.back
+        //                   ^
+        //                  cur
+        //
+        //
+        //    return.front -> A <-> B <- return.back
+        //
+        if let Some(cur) = self.cur {
+            // We are pointing at a real element, so the list is non-empty.
+            unsafe {
+                // Current state
+                let old_len = self.list.len;
+                let old_idx = self.index.unwrap();
+                let prev = (*cur.as_ptr()).front;
+
+                // What self will become
+                let new_len = old_len - old_idx;
+                let new_front = self.cur;
+                let new_back = self.list.back;
+                let new_idx = Some(0);
+
+                // What the output will become
+                let output_len = old_len - new_len;
+                // cur 是队首时输出为空链表, 不能带上 list.front
+                let output_front = prev.and(self.list.front);
+                let output_back = prev;
+
+                // Break the links between cur and prev
+                if let Some(prev) = prev {
+                    (*cur.as_ptr()).front = None;
+                    (*prev.as_ptr()).back = None;
+                }
+
+                // Produce the result:
+                self.list.len = new_len;
+                self.list.front = new_front;
+                self.list.back = new_back;
+                self.index = new_idx;
+
+                let mut output = LinkedList::from_nodes(output_front, output_back, output_len, self.list.alloc.clone());
+                self.list.handles.move_nodes(&mut output.handles, output_front, output_len);
+                self.debug_check();
+                output.debug_check();
+                output
+            }
+        } else {
+            // We're at the ghost, just move all nodes to a new list.
+            // No other state needs to be changed.
+            self.list.take_nodes()
+        }
+    }
+
+    pub fn split_after(&mut self) -> LinkedList<T, A> {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                         ^
+        //                        cur
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> B <- list.back
+        //                         ^
+        //                        cur
+        //
+        //
+        //    return.front -> C <-> D <- return.back
+        //
+        if let Some(cur) = self.cur {
+            // We are pointing at a real element, so the list is non-empty.
+            unsafe {
+                // Current state
+                let old_len = self.list.len;
+                let old_idx = self.index.unwrap();
+                let next = (*cur.as_ptr()).back;
+
+                // What self will become
+                let new_len = old_idx + 1;
+                let new_back = self.cur;
+                let new_front = self.list.front;
+                let new_idx = Some(old_idx);
+
+                // What the output will become
+                let output_len = old_len - new_len;
+                // cur 是队尾时输出为空链表, 不能带上 list.back
+                let output_front = next;
+                let output_back = next.and(self.list.back);
+
+                // Break the links between cur and next
+                if let Some(next) = next {
+                    (*cur.as_ptr()).back = None;
+                    (*next.as_ptr()).front = None;
+                }
+
+                // Produce the result:
+                self.list.len = new_len;
+                self.list.front = new_front;
+                self.list.back = new_back;
+                self.index = new_idx;
+
+                let mut output = LinkedList::from_nodes(output_front, output_back, output_len, self.list.alloc.clone());
+                self.list.handles.move_nodes(&mut output.handles, output_front, output_len);
+                self.debug_check();
+                output.debug_check();
+                output
+            }
+        } else {
+            // We're at the ghost, just move all nodes to a new list.
+            // No other state needs to be changed.
+            self.list.take_nodes()
+        }
+    }
+
+    /// 同 `remove_current`, 但不释放节点, 而是把它作为只有一个元素的链表返回
+    pub fn remove_current_as_list(&mut self) -> Option<LinkedList<T, A>> {
+        let output = self.unlink_current().map(|node| unsafe {
+            let mut output = LinkedList::from_nodes(Some(node), Some(node), 1, self.list.alloc.clone());
+            self.list.handles.move_nodes(&mut output.handles, Some(node), 1);
+            output
+        });
+        self.debug_check();
+        output
     }
 }
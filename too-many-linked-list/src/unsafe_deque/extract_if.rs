@@ -0,0 +1,43 @@
+use super::*;
+
+/// 由 `LinkedList::extract_if` 创建的惰性迭代器,
+/// 每次 `next` 向后查找, 把第一个满足条件的节点摘下并返回其值;
+/// 中途丢弃时, 剩下未检查的元素保持不变
+pub struct ExtractIf<'a, T, F, A: Allocator = Global>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    cursor: CursorMut<'a, T, A>,
+    pred: F,
+}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut cursor = self.cursor_mut();
+        // 从幽灵元素移到队首, 空链表时仍在幽灵元素上
+        cursor.move_next();
+        ExtractIf { cursor, pred }
+    }
+}
+
+impl<'a, T, F, A: Allocator> Iterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // 走到幽灵元素就结束, 不会再绕回队首
+        while let Some(elem) = self.cursor.current() {
+            if (self.pred)(elem) {
+                // 摘下后游标自动指向下一个节点
+                return self.cursor.remove_current();
+            }
+            self.cursor.move_next();
+        }
+        None
+    }
+}
@@ -0,0 +1,243 @@
+//! 节点句柄: push 时拿到指向节点的 `NodeHandle`, 之后 O(1) 访问, 删除, 移到队首
+//!
+//! 句柄只是一个裸指针, 有效性由调用者保证, 所以这些方法都是 `unsafe` 的, 前提写在各自的 `# Safety` 中;
+//! debug 模式下每个链表记录自己发出的, 仍然有效的句柄 (地址 -> 代数),
+//! 节点被释放或搬到别的链表时随之更新, 用过期的或其他链表的句柄时 panic.
+//! release 模式下不做任何检查, 违反前提就是未定义行为
+//!
+//! `get` `get_mut` `remove` `cursor_at` 已经是按下标访问的方法, 所以按句柄访问的版本加上
+//! `_by_handle` 后缀: `get_by_handle` `get_mut_by_handle` `remove_by_handle` `cursor_at_handle`;
+//! `move_to_front` `move_to_back` 没有重名, 保持原样
+use super::*;
+use core::hash::Hash;
+
+pub struct NodeHandle<T> {
+    node: NonNull<Node<T>>,
+    // 每次发出句柄都取一个新的代数, 节点内存被复用后旧句柄也能查出来
+    #[cfg(debug_assertions)]
+    generation: u64,
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeHandle<T> {}
+
+impl<T> PartialEq for NodeHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T> Eq for NodeHandle<T> {}
+
+impl<T> Hash for NodeHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.hash(state);
+    }
+}
+
+impl<T> Debug for NodeHandle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NodeHandle").field(&self.node).finish()
+    }
+}
+
+/// 句柄本身不能访问元素, 和下标一样可以随意跨线程传递
+unsafe impl<T> Send for NodeHandle<T> {}
+unsafe impl<T> Sync for NodeHandle<T> {}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// 同 `push_front`, 返回新节点的句柄
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list = LinkedList::new();
+    /// list.push_back(1);
+    /// let two = list.push_back_handle(2);
+    /// list.push_back(3);
+    /// unsafe {
+    ///     *list.get_mut_by_handle(two) *= 10;
+    ///     list.move_to_front(two);
+    ///     assert_eq!(list.iter().collect::<Vec<_>>(), [&20, &1, &3]);
+    ///     assert_eq!(list.remove_by_handle(two), 20);
+    /// }
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &3]);
+    /// ```
+    pub fn push_front_handle(&mut self, elem: T) -> NodeHandle<T> {
+        self.push_front(elem);
+        // SAFETY: 刚 push 过, 队首一定存在
+        unsafe { self.handles.issue(self.front.unwrap_unchecked()) }
+    }
+
+    /// 同 `push_back`, 返回新节点的句柄
+    pub fn push_back_handle(&mut self, elem: T) -> NodeHandle<T> {
+        self.push_back(elem);
+        // SAFETY: 刚 push 过, 队尾一定存在
+        unsafe { self.handles.issue(self.back.unwrap_unchecked()) }
+    }
+
+    /// O(1)
+    /// # Safety
+    /// `handle` 必须由 `push_front_handle` 或 `push_back_handle` 返回, 其节点现在在本链表中,
+    /// 且还没有被删除 (pop, `remove`, `remove_by_handle`, `clear`, `retain` 等).
+    /// 节点随 `split_off` `append` 等搬到别的链表后, 句柄跟着节点走, 属于新的链表
+    pub unsafe fn get_by_handle(&self, handle: NodeHandle<T>) -> &T {
+        self.handles.check(handle);
+        &(*handle.node.as_ptr()).elem
+    }
+
+    /// O(1)
+    /// # Safety
+    /// `handle` 必须由 `push_front_handle` 或 `push_back_handle` 返回, 其节点现在在本链表中且还没有被删除,
+    /// 详见 `get_by_handle`
+    pub unsafe fn get_mut_by_handle(&mut self, handle: NodeHandle<T>) -> &mut T {
+        self.handles.check(handle);
+        &mut (*handle.node.as_ptr()).elem
+    }
+
+    /// 删除句柄指向的元素并返回其值, 之后句柄失效, O(1)
+    /// # Safety
+    /// `handle` 必须由 `push_front_handle` 或 `push_back_handle` 返回, 其节点现在在本链表中且还没有被删除,
+    /// 详见 `get_by_handle`; 返回后这个句柄和它的所有副本都不能再使用
+    pub unsafe fn remove_by_handle(&mut self, handle: NodeHandle<T>) -> T {
+        self.handles.check(handle);
+        self.unlink_node(handle.node);
+        let elem = self.free_node(handle.node);
+        self.debug_check();
+        elem
+    }
+
+    /// 把句柄指向的元素移到队首, 句柄仍然有效, O(1)
+    /// # Safety
+    /// `handle` 必须由 `push_front_handle` 或 `push_back_handle` 返回, 其节点现在在本链表中且还没有被删除,
+    /// 详见 `get_by_handle`
+    pub unsafe fn move_to_front(&mut self, handle: NodeHandle<T>) {
+        self.handles.check(handle);
+        if self.front != Some(handle.node) {
+            self.unlink_node(handle.node);
+            self.link_node_before(handle.node, self.front);
+        }
+        self.debug_check();
+    }
+
+    /// 把句柄指向的元素移到队尾, 句柄仍然有效, O(1)
+    /// # Safety
+    /// `handle` 必须由 `push_front_handle` 或 `push_back_handle` 返回, 其节点现在在本链表中且还没有被删除,
+    /// 详见 `get_by_handle`
+    pub unsafe fn move_to_back(&mut self, handle: NodeHandle<T>) {
+        self.handles.check(handle);
+        if self.back != Some(handle.node) {
+            self.unlink_node(handle.node);
+            self.link_node_before(handle.node, None);
+        }
+        self.debug_check();
+    }
+
+    /// 在句柄指向的元素上创建游标
+    ///
+    /// 游标需要知道 index, 从节点同时向两端走, 先到达的一端决定 index,
+    /// 所以是 O(min(i, len - i)), 不是 O(1)
+    /// # Safety
+    /// `handle` 必须由 `push_front_handle` 或 `push_back_handle` 返回, 其节点现在在本链表中且还没有被删除,
+    /// 详见 `get_by_handle`; 用游标删除这个节点后句柄同样失效
+    pub unsafe fn cursor_at_handle(&mut self, handle: NodeHandle<T>) -> CursorMut<'_, T, A> {
+        self.handles.check(handle);
+        let (mut prev, mut next) = (Some(handle.node), Some(handle.node));
+        let mut steps = 0;
+        let index = loop {
+            prev = prev.and_then(|node| (*node.as_ptr()).front);
+            next = next.and_then(|node| (*node.as_ptr()).back);
+            if prev.is_none() {
+                break steps;
+            }
+            if next.is_none() {
+                break self.len - 1 - steps;
+            }
+            steps += 1;
+        };
+        CursorMut::at_node(self, handle.node, index)
+    }
+}
+
+/// 记录发出的仍然有效的句柄, 只在 debug 模式下工作, release 模式下是空的
+#[cfg(debug_assertions)]
+pub(super) struct Registry {
+    live: alloc::collections::BTreeMap<usize, u64>,
+}
+
+#[cfg(not(debug_assertions))]
+pub(super) struct Registry;
+
+#[cfg(debug_assertions)]
+impl Registry {
+    pub(super) const fn new() -> Self {
+        Self {
+            live: alloc::collections::BTreeMap::new(),
+        }
+    }
+
+    fn issue<T>(&mut self, node: NonNull<Node<T>>) -> NodeHandle<T> {
+        use core::sync::atomic::{AtomicU64, Ordering::Relaxed};
+        static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+        let generation = NEXT_GENERATION.fetch_add(1, Relaxed);
+        self.live.insert(node.as_ptr() as usize, generation);
+        NodeHandle { node, generation }
+    }
+
+    fn check<T>(&self, handle: NodeHandle<T>) {
+        assert_eq!(
+            self.live.get(&(handle.node.as_ptr() as usize)),
+            Some(&handle.generation),
+            "NodeHandle is stale or belongs to another list"
+        );
+    }
+
+    /// 节点被释放, 其句柄全部失效
+    pub(super) fn forget<T>(&mut self, node: NonNull<Node<T>>) {
+        if !self.live.is_empty() {
+            self.live.remove(&(node.as_ptr() as usize));
+        }
+    }
+
+    /// 所有节点都搬到了 `other` 所在的链表
+    pub(super) fn move_all(&mut self, other: &mut Self) {
+        other.live.append(&mut self.live);
+    }
+
+    /// 从 `front` 开始沿 `back` 方向的 `len` 个节点搬到了 `other` 所在的链表
+    pub(super) unsafe fn move_nodes<T>(&mut self, other: &mut Self, mut node: Link<T>, len: usize) {
+        for _ in 0..len {
+            if self.live.is_empty() {
+                break;
+            }
+            let cur = node.unwrap();
+            if let Some(generation) = self.live.remove(&(cur.as_ptr() as usize)) {
+                other.live.insert(cur.as_ptr() as usize, generation);
+            }
+            node = (*cur.as_ptr()).back;
+        }
+    }
+}
+
+#[cfg(not(debug_assertions))]
+impl Registry {
+    pub(super) const fn new() -> Self {
+        Self
+    }
+
+    fn issue<T>(&mut self, node: NonNull<Node<T>>) -> NodeHandle<T> {
+        NodeHandle { node }
+    }
+
+    fn check<T>(&self, _handle: NodeHandle<T>) {}
+
+    pub(super) fn forget<T>(&mut self, _node: NonNull<Node<T>>) {}
+
+    pub(super) fn move_all(&mut self, _other: &mut Self) {}
+
+    pub(super) unsafe fn move_nodes<T>(&mut self, _other: &mut Self, _node: Link<T>, _len: usize) {}
+}
@@ -0,0 +1,50 @@
+//! 不变量检查, 裸指针改链之后用来确认链表没有被改坏
+//!
+//! 开启 `debug-invariants` feature 后, 每个修改链表的公开方法结束前都会调用一次,
+//! 出错时 panic 在第一次破坏不变量的地方, 而不是之后某次访问悬垂指针时
+use super::*;
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// 检查所有不变量, 不满足时 panic, O(n)
+    ///
+    /// 1. `front` `back` `len` 三者同时为空
+    /// 2. 队首没有 `front`, 队尾没有 `back`
+    /// 3. 每个节点的 `back` 的 `front` 指回自己
+    /// 4. 从 `front` 沿 `back` 走 `len` 步恰好走到 `back`
+    /// 5. 空闲节点个数等于 `spare_len`
+    pub fn check_invariants(&self) {
+        match (self.front, self.back) {
+            (None, None) => assert_eq!(self.len, 0, "empty list with non-zero len"),
+            (Some(front), Some(back)) => unsafe {
+                assert!((*front.as_ptr()).front.is_none(), "front node has a front link");
+                assert!((*back.as_ptr()).back.is_none(), "back node has a back link");
+                let mut count = 1;
+                let mut node = front;
+                while let Some(next) = (*node.as_ptr()).back {
+                    assert_eq!((*next.as_ptr()).front, Some(node), "front/back links do not mirror each other");
+                    node = next;
+                    count += 1;
+                    assert!(count <= self.len, "more nodes than len {}", self.len);
+                }
+                assert_eq!(node, back, "walking from front does not end at back");
+                assert_eq!(count, self.len, "len does not match node count");
+            },
+            _ => panic!("only one of front and back is set"),
+        }
+        let mut spare = 0;
+        let mut node = self.spare;
+        while let Some(cur) = node {
+            spare += 1;
+            assert!(spare <= self.spare_len, "more spare nodes than spare_len {}", self.spare_len);
+            node = unsafe { (*cur.as_ptr()).back };
+        }
+        assert_eq!(spare, self.spare_len, "spare_len does not match spare node count");
+    }
+
+    /// `debug-invariants` 开启时检查不变量, 否则什么都不做
+    #[inline(always)]
+    pub(super) fn debug_check(&self) {
+        #[cfg(feature = "debug-invariants")]
+        self.check_invariants();
+    }
+}
@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 use super::*;
 
 pub struct Iter<'a, T> {
@@ -8,12 +8,21 @@ pub struct Iter<'a, T> {
     _boo: PhantomData<&'a T>,
 }
 
-impl<'a, T> From<&LinkedList<T>> for Iter<'a, T> {
-    fn from(l: &LinkedList<T>) -> Self {
+impl<'a, T, A: Allocator> From<&LinkedList<T, A>> for Iter<'a, T> {
+    fn from(l: &LinkedList<T, A>) -> Self {
+        unsafe { Self::from_raw(l.front, l.back, l.len) }
+    }
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// 从 `front` 到 `back` 共 `len` 个连续节点的视图
+    /// # Safety
+    /// 这段节点在 `'a` 内有效, 且不会被别处修改
+    pub(super) unsafe fn from_raw(front: Link<T>, back: Link<T>, len: usize) -> Self {
         Self {
-            front: l.front,
-            back: l.back,
-            len: l.len,
+            front,
+            back,
+            len,
             _boo: PhantomData,
         }
     }
@@ -41,6 +50,19 @@ impl<'a, T> Iterator for Iter<'a, T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.len, Some(self.len))
     }
+
+    // 只沿指针跳过 n 个节点, 不逐个产出引用
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.len {
+            self.len = 0;
+            return None;
+        }
+        for _ in 0..n {
+            self.front = self.front.and_then(|node| unsafe { (*node.as_ptr()).back });
+        }
+        self.len -= n;
+        self.next()
+    }
 }
 
 impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
@@ -55,6 +77,18 @@ impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
             None
         }
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.len {
+            self.len = 0;
+            return None;
+        }
+        for _ in 0..n {
+            self.back = self.back.and_then(|node| unsafe { (*node.as_ptr()).front });
+        }
+        self.len -= n;
+        self.next_back()
+    }
 }
 
 impl<'a, T> ExactSizeIterator for Iter<'a, T> {
@@ -63,5 +97,13 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
-unsafe impl<'a, T: Send> Send for Iter<'a, T> {}
+// 手写而不 derive, 免得要求 `T: Clone`
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+// 与 `&T` 一样, 把 `Iter` 交给别的线程只要求 `T: Sync`
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
 unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
@@ -1,4 +1,7 @@
 //! [7. A Production Unsafe Deque](https://rust-unofficial.github.io/too-many-lists/sixth-final.html)
+//! 节点通过 `allocator-api2` 的 `Allocator` 分配, 默认为 `Global`, 在 stable 上即可使用
+//! 节点在链表之间搬运时 (split splice append 等), 要求双方的分配器可以互相释放对方分配的内存,
+//! 比如同一个分配器的 clone, 与标准库一致
 
 mod variance;
 mod ptr_mut;
@@ -8,6 +11,17 @@ mod iter;
 mod iter_mut;
 mod into_iter;
 mod cursor;
+mod extract_if;
+mod views;
+#[cfg(feature = "std")]
+mod par;
+mod sort;
+mod sorted;
+mod pool;
+mod handle;
+mod invariants;
+#[cfg(feature = "serde")]
+mod serde;
 
 #[cfg(test)]
 mod test;
@@ -15,21 +29,37 @@ mod test;
 pub use self::iter::Iter;
 pub use self::iter_mut::IterMut;
 pub use self::into_iter::IntoIter;
+pub use self::cursor::Cursor;
 pub use self::cursor::CursorMut;
+pub use self::extract_if::ExtractIf;
+pub use self::views::{Chunks, Windows};
+pub use self::handle::NodeHandle;
+pub use self::sorted::{Difference, Duplicates, Intersection, SortedLinkedList};
 
 use core::fmt::Debug;
 use core::cmp::Ordering;
 use core::hash::Hash;
 use core::hash::Hasher;
 use core::iter::FromIterator;
+use core::ops::{Index, IndexMut};
 
 use core::ptr::NonNull;
 use core::marker::PhantomData;
+pub use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::boxed::Box;
 
-pub struct LinkedList<T> {
+pub struct LinkedList<T, A: Allocator = Global> {
     front: Link<T>,
     back: Link<T>,
     len: usize,
+    alloc: A,
+    // 回收的空闲节点, 只用 `back` 串成单链表, 其中的 `elem` 未初始化
+    spare: Link<T>,
+    spare_len: usize,
+    // 为 true 时 pop 出的节点放入 `spare` 而不释放, 见 `pool.rs`
+    recycle: bool,
+    // debug 模式下记录发出的句柄, 见 `handle.rs`
+    handles: handle::Registry,
     // We semantically store values of T by-value. 非必要
     _boo: PhantomData<T>,
 }
@@ -53,23 +83,34 @@ struct Node<T> {
 }
 
 impl<T> LinkedList<T> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    pub const fn new_in(alloc: A) -> Self {
         Self {
             front: None,
             back: None,
             len: 0,
+            alloc,
+            spare: None,
+            spare_len: 0,
+            recycle: false,
+            handles: handle::Registry::new(),
             _boo: PhantomData,
         }
     }
 
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     pub fn push_front(&mut self, elem: T) {
         // SAFETY: it's a linked-list, what do you want?
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                front: None,
-                back: None,
-                elem,
-            })));
+            let new = self.new_node(elem);
             if let Some(old) = self.front {
                 // Put the new front before the old one
                 (*old.as_ptr()).front = Some(new);
@@ -83,16 +124,13 @@ impl<T> LinkedList<T> {
             self.front = Some(new);
             self.len += 1;
         }
+        self.debug_check();
     }
 
     pub fn push_back(&mut self, elem: T) {
         // SAFETY: it's a linked-list, what do you want?
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                back: None,
-                front: None,
-                elem,
-            })));
+            let new = self.new_node(elem);
             if let Some(old) = self.back {
                 // Put the new back before the old one
                 (*old.as_ptr()).back = Some(new);
@@ -106,19 +144,15 @@ impl<T> LinkedList<T> {
             self.back = Some(new);
             self.len += 1;
         }
+        self.debug_check();
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
-        unsafe {
+        let elem = unsafe {
             // Only have to do stuff if there is a front node to pop.
             self.front.map(|node| {
-                // Bring the Box back to life so we can move out its value and
-                // Drop it (Box continues to magically understand this for us).
-                let boxed_node = Box::from_raw(node.as_ptr());
-                let result = boxed_node.elem;
-
                 // Make the next node into the new front.
-                self.front = boxed_node.back;
+                self.front = (*node.as_ptr()).back;
                 if let Some(new) = self.front {
                     // Cleanup its reference to the removed node
                     (*new.as_ptr()).front = None;
@@ -128,23 +162,20 @@ impl<T> LinkedList<T> {
                 }
 
                 self.len -= 1;
-                result
-                // Box gets implicitly freed here, knows there is no T.
+                // Move out its value, then free or recycle the node.
+                self.free_node(node)
             })
-        }
+        };
+        self.debug_check();
+        elem
     }
 
     pub fn pop_back(&mut self) -> Option<T> {
-        unsafe {
+        let elem = unsafe {
             // Only have to do stuff if there is a back node to pop.
             self.back.map(|node| {
-                // Bring the Box front to life so we can move out its value and
-                // Drop it (Box continues to magically understand this for us).
-                let boxed_node = Box::from_raw(node.as_ptr());
-                let result = boxed_node.elem;
-
                 // Make the next node into the new back.
-                self.back = boxed_node.front;
+                self.back = (*node.as_ptr()).front;
                 if let Some(new) = self.back {
                     // Cleanup its reference to the removed node
                     (*new.as_ptr()).back = None;
@@ -154,10 +185,12 @@ impl<T> LinkedList<T> {
                 }
 
                 self.len -= 1;
-                result
-                // Box gets implicitly freed here, knows there is no T.
+                // Move out its value, then free or recycle the node.
+                self.free_node(node)
             })
-        }
+        };
+        self.debug_check();
+        elem
     }
 
     pub fn front(&self) -> Option<&T> {
@@ -176,6 +209,61 @@ impl<T> LinkedList<T> {
         unsafe { self.back.map(|node| &mut (*node.as_ptr()).elem) }
     }
 
+    /// 下标为 `index` 的元素, 从较近的一端开始走, O(min(i, len - i))
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list: LinkedList<_> = (0..5).collect();
+    /// assert_eq!(list.get(3), Some(&3));
+    /// assert_eq!(list.get(5), None);
+    /// list.insert(1, 10);
+    /// assert_eq!(list.remove(4), Some(3));
+    /// list[0] = -1;
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [&-1, &10, &1, &2, &4]);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            unsafe { Some(&(*self.node_at(index).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            unsafe { Some(&mut (*self.node_at(index).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    /// 插入到下标 `index` 处, 之后的元素后移, `index == len` 时等同 `push_back`
+    /// # Panics
+    /// `index > len` 时 panic
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(index <= self.len, "insertion index (is {}) should be <= len (is {})", index, self.len);
+        let next = if index < self.len { Some(self.node_at(index)) } else { None };
+        unsafe {
+            let new = self.new_node(elem);
+            self.link_node_before(new, next);
+        }
+        self.debug_check();
+    }
+
+    /// 删除下标为 `index` 的元素并返回, 越界时返回 `None`
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let elem = unsafe {
+            let node = self.node_at(index);
+            self.unlink_node(node);
+            self.free_node(node)
+        };
+        self.debug_check();
+        Some(elem)
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
@@ -189,36 +277,290 @@ impl<T> LinkedList<T> {
         while self.pop_front().is_some() {}
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.into()
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         self.into()
     }
 
-    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+    pub fn cursor(&self) -> Cursor<'_, T, A> {
         self.into()
     }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, A> {
+        self.into()
+    }
+
+    /// 停在下标 `index` 上的游标, `index == len` 时停在幽灵元素上
+    /// # Panics
+    /// `index > len` 时 panic
+    pub fn cursor_at(&mut self, index: usize) -> CursorMut<'_, T, A> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_to(index);
+        cursor
+    }
+
+    /// 把 `other` 的所有节点接到队尾, `other` 变为空, O(1)
+    pub fn append(&mut self, other: &mut Self) {
+        // 幽灵元素上 splice_before 就是接到队尾
+        self.cursor_mut().splice_before_from(other);
+        self.debug_check();
+        other.debug_check();
+    }
+
+    /// 只保留 `f` 返回 `true` 的元素, 原地摘除其余节点
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.extract_if(|elem| !f(elem)).for_each(drop);
+    }
+
+    /// 原地反转, 只交换每个节点的前后指针, 节点本身不动, O(n)
+    pub fn reverse(&mut self) {
+        let mut cur = self.front;
+        while let Some(node) = cur {
+            unsafe {
+                let node = &mut *node.as_ptr();
+                core::mem::swap(&mut node.front, &mut node.back);
+                // 交换后原来的下一个节点在 `front` 里
+                cur = node.front;
+            }
+        }
+        core::mem::swap(&mut self.front, &mut self.back);
+        self.debug_check();
+    }
+
+    /// 循环左移 `k` 位, 原来下标为 `k` 的元素成为队首
+    /// 首尾相接后在新的断点处断开, 断点从较近的一端找, O(min(k, len - k))
+    /// # Panics
+    /// `k > len` 时 panic
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list: LinkedList<_> = (0..5).collect();
+    /// list.rotate_left(2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [&2, &3, &4, &0, &1]);
+    /// list.rotate_right(1);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3, &4, &0]);
+    /// ```
+    pub fn rotate_left(&mut self, k: usize) {
+        assert!(k <= self.len, "rotate amount {} out of bounds for length {}", k, self.len);
+        if k == 0 || k == self.len {
+            return;
+        }
+        let new_front = self.node_at(k);
+        unsafe {
+            // 0 < k < len, 这几个节点都存在
+            let new_back = (*new_front.as_ptr()).front.unwrap_unchecked();
+            let (old_front, old_back) = (self.front.unwrap_unchecked(), self.back.unwrap_unchecked());
+            (*old_back.as_ptr()).back = Some(old_front);
+            (*old_front.as_ptr()).front = Some(old_back);
+            (*new_back.as_ptr()).back = None;
+            (*new_front.as_ptr()).front = None;
+            self.front = Some(new_front);
+            self.back = Some(new_back);
+        }
+        self.debug_check();
+    }
+
+    /// 循环右移 `k` 位, 原来的后 `k` 个元素移到队首
+    /// # Panics
+    /// `k > len` 时 panic
+    pub fn rotate_right(&mut self, k: usize) {
+        assert!(k <= self.len, "rotate amount {} out of bounds for length {}", k, self.len);
+        self.rotate_left(self.len - k);
+    }
+
+    /// 交换下标 `i` 和 `j` 处的节点, 重新链接而不是交换值, 指向节点的句柄仍然有效
+    /// # Panics
+    /// 任一下标越界时 panic
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(i < self.len, "index {} out of bounds for length {}", i, self.len);
+        assert!(j < self.len, "index {} out of bounds for length {}", j, self.len);
+        if i == j {
+            return;
+        }
+        let (a, b) = (self.node_at(i.min(j)), self.node_at(i.max(j)));
+        unsafe {
+            // a 在 b 之前: 先把 b 挪到 a 前面, 再把 a 挪到 b 原来的位置
+            // 两者相邻时 after_b 不受影响, 照样成立
+            let after_b = (*b.as_ptr()).back;
+            self.unlink_node(b);
+            self.link_node_before(b, Some(a));
+            self.unlink_node(a);
+            self.link_node_before(a, after_b);
+        }
+        self.debug_check();
+    }
 }
 
-impl<T> Drop for LinkedList<T> {
+impl<T, A: Allocator + Clone> LinkedList<T, A> {
+    /// 从 `at` 处一分为二, `self` 保留 `[0, at)`, 返回 `[at, len)`
+    /// 从离 `at` 较近的一端开始走
+    /// # Panics
+    /// `at > len` 时 panic
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            return self.take_nodes();
+        }
+        let len = self.len;
+        let mut cursor = self.cursor_mut();
+        // 游标停在 `at - 1` 上, 之后的都切走
+        if at - 1 < len / 2 {
+            for _ in 0..at {
+                cursor.move_next();
+            }
+        } else {
+            for _ in 0..len - at + 1 {
+                cursor.move_prev();
+            }
+        }
+        cursor.split_after()
+    }
+
+    /// 把所有节点移到一个新链表中返回, 空闲节点和回收模式仍留在 `self`
+    fn take_nodes(&mut self) -> Self {
+        let len = core::mem::replace(&mut self.len, 0);
+        let mut list = unsafe { Self::from_nodes(self.front.take(), self.back.take(), len, self.alloc.clone()) };
+        self.handles.move_all(&mut list.handles);
+        self.debug_check();
+        list.debug_check();
+        list
+    }
+}
+
+/// 节点级的辅助函数, 供 `CursorMut` 等直接操作节点
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// 用一段已经首尾相连的节点组成新链表, 这些节点必须能用 `alloc` 释放
+    unsafe fn from_nodes(front: Link<T>, back: Link<T>, len: usize, alloc: A) -> Self {
+        let mut list = Self::new_in(alloc);
+        list.front = front;
+        list.back = back;
+        list.len = len;
+        list
+    }
+
+    /// 用本链表的分配器分配一个孤立的节点, 有空闲节点时优先复用
+    fn new_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        if let Some(node) = self.pop_spare() {
+            // SAFETY: 空闲节点的内存有效, 其中的 `elem` 未初始化, 直接整体覆盖
+            unsafe {
+                node.as_ptr().write(Node {
+                    front: None,
+                    back: None,
+                    elem,
+                });
+            }
+            return node;
+        }
+        let node = Box::new_in(Node {
+            front: None,
+            back: None,
+            elem,
+        }, &self.alloc);
+        let (ptr, _) = Box::into_raw_with_allocator(node);
+        // SAFETY: Box::into_raw_with_allocator 不会返回空指针
+        unsafe { NonNull::new_unchecked(ptr) }
+    }
+
+    /// 释放一个已经摘下的节点, 取出其中的值; 回收模式下节点放入空闲链表
+    unsafe fn free_node(&mut self, node: NonNull<Node<T>>) -> T {
+        self.handles.forget(node);
+        if self.recycle {
+            let elem = core::ptr::read(&(*node.as_ptr()).elem);
+            self.push_spare(node);
+            elem
+        } else {
+            Box::into_inner(Box::from_raw_in(node.as_ptr(), &self.alloc)).elem
+        }
+    }
+
+    /// 把一个孤立的节点 `new` 挂在 `next` 之前, `next` 为 `None` 时挂在队尾
+    unsafe fn link_node_before(&mut self, new: NonNull<Node<T>>, next: Link<T>) {
+        let prev = match next {
+            Some(next) => (*next.as_ptr()).front,
+            None => self.back,
+        };
+        (*new.as_ptr()).front = prev;
+        (*new.as_ptr()).back = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).back = Some(new),
+            None => self.front = Some(new),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).front = Some(new),
+            None => self.back = Some(new),
+        }
+        self.len += 1;
+    }
+
+    /// 下标为 `index` 的节点, 从较近的一端开始走, 要求 `index < len`
+    fn node_at(&self, index: usize) -> NonNull<Node<T>> {
+        debug_assert!(index < self.len);
+        // SAFETY: index < len, 沿途的节点都存在
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.front.unwrap_unchecked();
+                for _ in 0..index {
+                    node = (*node.as_ptr()).back.unwrap_unchecked();
+                }
+                node
+            } else {
+                let mut node = self.back.unwrap_unchecked();
+                for _ in index + 1..self.len {
+                    node = (*node.as_ptr()).front.unwrap_unchecked();
+                }
+                node
+            }
+        }
+    }
+
+    /// 把节点 `node` 从链表中摘下, 不释放, 摘下后 `node` 的前后指针都清空
+    unsafe fn unlink_node(&mut self, node: NonNull<Node<T>>) {
+        let prev = (*node.as_ptr()).front.take();
+        let next = (*node.as_ptr()).back.take();
+        match prev {
+            Some(prev) => (*prev.as_ptr()).back = next,
+            None => self.front = next,
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).front = prev,
+            None => self.back = prev,
+        }
+        self.len -= 1;
+    }
+}
+
+impl<T, A: Allocator> Drop for LinkedList<T, A> {
     fn drop(&mut self) {
         // Pop until we have to stop
         //while self.pop_front().is_some() {}
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
+        // pop 出的节点可能被回收了, 最后统一释放
+        self.shrink_to_fit();
     }
 }
 
-impl<T> Default for LinkedList<T> {
+impl<T, A: Allocator + Default> Default for LinkedList<T, A> {
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
-impl<T: Clone> Clone for LinkedList<T> {
+impl<T: Clone, A: Allocator + Clone> Clone for LinkedList<T, A> {
     fn clone(&self) -> Self {
-        let mut new_list = Self::new();
+        let mut new_list = Self::new_in(self.alloc.clone());
         for item in self {
             new_list.push_back(item.clone());
         }
@@ -226,7 +568,7 @@ impl<T: Clone> Clone for LinkedList<T> {
     }
 }
 
-impl<T> Extend<T> for LinkedList<T> {
+impl<T, A: Allocator> Extend<T> for LinkedList<T, A> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
             self.push_back(item);
@@ -242,33 +584,33 @@ impl<T> FromIterator<T> for LinkedList<T> {
     }
 }
 
-impl<T: Debug> Debug for LinkedList<T> {
+impl<T: Debug, A: Allocator> Debug for LinkedList<T, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for LinkedList<T> {
+impl<T: PartialEq, A: Allocator> PartialEq for LinkedList<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
 }
 
-impl<T: Eq> Eq for LinkedList<T> {}
+impl<T: Eq, A: Allocator> Eq for LinkedList<T, A> {}
 
-impl<T: PartialOrd> PartialOrd for LinkedList<T> {
+impl<T: PartialOrd, A: Allocator> PartialOrd for LinkedList<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.iter().partial_cmp(other)
     }
 }
 
-impl<T: Ord> Ord for LinkedList<T> {
+impl<T: Ord, A: Allocator> Ord for LinkedList<T, A> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.iter().cmp(other)
     }
 }
 
-impl<T: Hash> Hash for LinkedList<T> {
+impl<T: Hash, A: Allocator> Hash for LinkedList<T, A> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -277,5 +619,23 @@ impl<T: Hash> Hash for LinkedList<T> {
     }
 }
 
-unsafe impl<T: Send> Send for LinkedList<T> {}
-unsafe impl<T: Sync> Sync for LinkedList<T> {}
+impl<T, A: Allocator> Index<usize> for LinkedList<T, A> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, index))
+    }
+}
+
+impl<T, A: Allocator> IndexMut<usize> for LinkedList<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, index))
+    }
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for LinkedList<T, A> {}
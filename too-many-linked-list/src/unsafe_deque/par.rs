@@ -0,0 +1,132 @@
+//! 基于 `std::thread::scope` 的并行遍历
+//!
+//! 先沿链表走一遍, 按线程数切成互不相交的几段, 每段是一个 `Iter`/`IterMut` 视图,
+//! 交给各自的线程处理, 元素本身不复制也不搬动; 当前线程也处理其中一段
+use core::mem::MaybeUninit;
+use std::thread;
+use super::*;
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// 并行地对每个元素调用 `f`, 调用顺序不确定
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// let list: LinkedList<u64> = (1..=1000).collect();
+    /// let sum = AtomicU64::new(0);
+    /// list.par_for_each(|x| {
+    ///     sum.fetch_add(*x, Ordering::Relaxed);
+    /// });
+    /// assert_eq!(sum.into_inner(), 500500);
+    /// ```
+    pub fn par_for_each<F>(&self, f: F)
+    where
+        T: Sync,
+        F: Fn(&T) + Sync,
+    {
+        let f = &f;
+        thread::scope(|s| {
+            let mut parts = self.split_parts().into_iter();
+            let last = parts.next_back();
+            for (front, back, len) in parts {
+                let part = unsafe { Iter::from_raw(front, back, len) };
+                s.spawn(move || part.for_each(f));
+            }
+            if let Some((front, back, len)) = last {
+                unsafe { Iter::from_raw(front, back, len) }.for_each(f);
+            }
+        });
+    }
+
+    /// 并行地对每个元素调用 `f`, 可以原地修改元素
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list: LinkedList<u64> = (0..100).collect();
+    /// list.par_for_each_mut(|x| *x *= 2);
+    /// assert!(list.iter().copied().eq((0..100).map(|x| x * 2)));
+    /// ```
+    pub fn par_for_each_mut<F>(&mut self, f: F)
+    where
+        T: Send,
+        F: Fn(&mut T) + Sync,
+    {
+        let f = &f;
+        // 各段互不相交, 整个过程中 `&mut self` 保证没有别的访问
+        thread::scope(|s| {
+            let mut parts = self.split_parts().into_iter();
+            let last = parts.next_back();
+            for (front, back, len) in parts {
+                let part = unsafe { IterMut::from_raw(front, back, len) };
+                s.spawn(move || part.for_each(f));
+            }
+            if let Some((front, back, len)) = last {
+                unsafe { IterMut::from_raw(front, back, len) }.for_each(f);
+            }
+        });
+    }
+
+    /// 并行地把每个元素映射为 `U`, 按链表顺序收集到 `Vec` 中
+    ///
+    /// 结果直接写入 `Vec` 中与该段对应的位置, 不经过中间的 `Vec`;
+    /// `f` panic 时 panic 会传到调用方, 已经算出的结果被泄漏而不会 drop
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let list: LinkedList<u32> = (0..10).collect();
+    /// assert_eq!(list.par_map_into_vec(|x| x.to_string()).concat(), "0123456789");
+    /// ```
+    pub fn par_map_into_vec<U, F>(&self, f: F) -> Vec<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync,
+    {
+        fn fill<T, U>(part: Iter<'_, T>, out: &mut [MaybeUninit<U>], f: &impl Fn(&T) -> U) {
+            for (slot, elem) in out.iter_mut().zip(part) {
+                slot.write(f(elem));
+            }
+        }
+
+        let mut vec = Vec::with_capacity(self.len);
+        let f = &f;
+        thread::scope(|s| {
+            let mut rest = &mut vec.spare_capacity_mut()[..self.len];
+            let mut parts = self.split_parts().into_iter();
+            let last = parts.next_back();
+            for (front, back, len) in parts {
+                let part = unsafe { Iter::from_raw(front, back, len) };
+                let (out, tail) = core::mem::take(&mut rest).split_at_mut(len);
+                rest = tail;
+                s.spawn(move || fill(part, out, f));
+            }
+            if let Some((front, back, len)) = last {
+                fill(unsafe { Iter::from_raw(front, back, len) }, rest, f);
+            }
+        });
+        // SAFETY: 所有线程都正常结束, 前 len 个位置都已写入
+        unsafe { vec.set_len(self.len) };
+        vec
+    }
+
+    /// 按可用的线程数把链表切成几段, 返回每段的首尾节点和长度, 各段长度至多相差 1
+    fn split_parts(&self) -> Vec<(Link<T>, Link<T>, usize)> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let count = threads.min(self.len);
+        let mut parts = Vec::with_capacity(count);
+        let mut node = self.front;
+        for i in 0..count {
+            let len = self.len / count + usize::from(i < self.len % count);
+            let front = node;
+            let mut back = front;
+            unsafe {
+                for _ in 1..len {
+                    back = (*back.unwrap_unchecked().as_ptr()).back;
+                }
+                node = (*back.unwrap_unchecked().as_ptr()).back;
+            }
+            parts.push((front, back, len));
+        }
+        parts
+    }
+}
@@ -0,0 +1,106 @@
+//! 节点池: 回收 pop 出的节点, 下次 push 时复用, 减少频繁进出队列时的分配
+//!
+//! 默认不回收, 用 `with_capacity` 创建或调用 `set_recycle(true)` 后进入回收模式,
+//! 此后 pop 出的节点都留在空闲链表中; `shrink_to_fit` 只释放空闲节点,
+//! 像 `Vec::shrink_to_fit` 一样不改变之后的行为, 关闭回收要显式调用 `set_recycle(false)`
+use super::*;
+use allocator_api2::alloc::{handle_alloc_error, Layout};
+
+impl<T> LinkedList<T> {
+    /// 预先分配 `capacity` 个节点, 并开启回收模式
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list = LinkedList::with_capacity(4);
+    /// assert_eq!(list.capacity(), 4);
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.spare_capacity(), 2);
+    /// list.pop_front();
+    /// assert_eq!(list.spare_capacity(), 3, "popped node is kept");
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut list = Self::new_in(alloc);
+        list.recycle = true;
+        list.reserve(capacity);
+        list
+    }
+
+    /// 元素个数加上空闲节点个数
+    pub fn capacity(&self) -> usize {
+        self.len + self.spare_len
+    }
+
+    /// 空闲节点个数, 这么多次 push 不会再分配内存
+    pub fn spare_capacity(&self) -> usize {
+        self.spare_len
+    }
+
+    /// 是否处于回收模式
+    pub fn is_recycling(&self) -> bool {
+        self.recycle
+    }
+
+    /// 开启或关闭回收模式; 关闭后 pop 出的节点直接释放, 已有的空闲节点仍会被 push 用掉
+    /// # Examples
+    /// ```
+    /// use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let mut list = LinkedList::new();
+    /// list.set_recycle(true);
+    /// list.push_back(1);
+    /// list.pop_back();
+    /// assert_eq!(list.spare_capacity(), 1);
+    /// list.set_recycle(false);
+    /// list.push_back(2);
+    /// list.pop_back();
+    /// assert_eq!(list.spare_capacity(), 0);
+    /// ```
+    pub fn set_recycle(&mut self, recycle: bool) {
+        self.recycle = recycle;
+    }
+
+    /// 保证至少还能再 push `additional` 个元素而不分配内存, 不改变回收模式
+    pub fn reserve(&mut self, additional: usize) {
+        let layout = Layout::new::<Node<T>>();
+        while self.spare_len < additional {
+            let node = match self.alloc.allocate(layout) {
+                Ok(ptr) => ptr.cast::<Node<T>>(),
+                Err(_) => handle_alloc_error(layout),
+            };
+            unsafe { self.push_spare(node) };
+        }
+        self.debug_check();
+    }
+
+    /// 释放所有空闲节点, 不改变回收模式
+    pub fn shrink_to_fit(&mut self) {
+        let layout = Layout::new::<Node<T>>();
+        while let Some(node) = self.pop_spare() {
+            // SAFETY: 空闲节点中的 `elem` 已经被移走, 只释放内存, 不能 drop
+            unsafe { self.alloc.deallocate(node.cast(), layout) };
+        }
+        self.debug_check();
+    }
+
+    /// 放入空闲链表, `node` 中的 `elem` 必须已经移走或未初始化
+    pub(super) unsafe fn push_spare(&mut self, node: NonNull<Node<T>>) {
+        core::ptr::addr_of_mut!((*node.as_ptr()).back).write(self.spare);
+        self.spare = Some(node);
+        self.spare_len += 1;
+    }
+
+    pub(super) fn pop_spare(&mut self) -> Link<T> {
+        let node = self.spare?;
+        unsafe {
+            self.spare = (*node.as_ptr()).back;
+        }
+        self.spare_len -= 1;
+        Some(node)
+    }
+}
@@ -1,4 +1,6 @@
-use std::{ptr::null_mut, marker::PhantomData};
+#![allow(dead_code)]
+use core::ptr::null_mut;
+use alloc::boxed::Box;
 
 pub struct LinkedList<T> {
     front: Link<T>,
@@ -0,0 +1,35 @@
+//! 序列化为从队首到队尾的序列, 不包含分配器和节点池的状态
+use super::*;
+use core::fmt;
+use ::serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+
+impl<T: Serialize, A: Allocator> Serialize for LinkedList<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, A: Allocator + Default> Deserialize<'de> for LinkedList<T, A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ListVisitor<T, A>(PhantomData<(T, A)>);
+
+        impl<'de, T: Deserialize<'de>, A: Allocator + Default> Visitor<'de> for ListVisitor<T, A> {
+            type Value = LinkedList<T, A>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let mut list = LinkedList::default();
+                while let Some(elem) = seq.next_element()? {
+                    list.push_back(elem);
+                }
+                Ok(list)
+            }
+        }
+
+        deserializer.deserialize_seq(ListVisitor(PhantomData))
+    }
+}
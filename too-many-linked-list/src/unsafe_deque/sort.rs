@@ -0,0 +1,190 @@
+//! 原地归并排序, 只改节点的指针, 不移动元素, 不额外分配内存
+use super::*;
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// 稳定排序
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// 自底向上的归并排序, 稳定, O(n log n)
+    ///
+    /// 排序时只沿 `back` 方向当作单链表处理, 最后统一修复 `front` 指针;
+    /// `compare` panic 时所有节点仍留在链表中, 只是顺序不定
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.len < 2 {
+            return;
+        }
+        unsafe {
+            let rest = self.front.take();
+            self.back = None;
+            let mut runs = Detached::new(self, rest);
+            while let Some(node) = runs.rest {
+                runs.rest = (*node.as_ptr()).back.take();
+                runs.carry = Some(node);
+                for i in 0..runs.bins.len() {
+                    if runs.bins[i].is_none() {
+                        runs.bins[i] = runs.carry.take();
+                        break;
+                    }
+                    // bin 中的元素在 carry 之前, 保证稳定
+                    let (left, right) = (runs.bins[i].take(), runs.carry.take());
+                    merge(left, right, &mut runs.carry, &mut compare);
+                }
+            }
+            // 最后把所有 bin 合并到 carry, 下标越大的元素越靠前
+            for i in 0..runs.bins.len() {
+                if runs.bins[i].is_some() {
+                    let (left, right) = (runs.bins[i].take(), runs.carry.take());
+                    merge(left, right, &mut runs.carry, &mut compare);
+                }
+            }
+        }
+        self.debug_check();
+    }
+
+    /// 合并两个已经有序的链表, `other` 的节点全部移入 `self`, O(n + m)
+    /// 相等的元素 `self` 的在前
+    pub fn merge_sorted(&mut self, other: Self)
+    where
+        T: Ord,
+    {
+        self.merge_sorted_by(other, T::cmp);
+    }
+
+    /// `compare` panic 时两个链表的节点都留在 `self` 中, 只是顺序不定
+    pub fn merge_sorted_by<F>(&mut self, mut other: Self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if other.is_empty() {
+            return;
+        }
+        unsafe {
+            let left = self.front.take();
+            self.back = None;
+            let right = other.front.take();
+            other.back = None;
+            self.len += core::mem::replace(&mut other.len, 0);
+            other.handles.move_all(&mut self.handles);
+            let mut runs = Detached::new(self, None);
+            merge(left, right, &mut runs.carry, &mut compare);
+        }
+        self.debug_check();
+    }
+
+    /// `head` 是只有 `back` 指针有效的单链表, 长度为 `self.len`,
+    /// 补全所有 `front` 指针, 并设置 `self.front` `self.back`
+    unsafe fn relink_front(&mut self, head: Link<T>) {
+        let mut prev = None;
+        let mut cur = head;
+        while let Some(node) = cur {
+            (*node.as_ptr()).front = prev;
+            prev = cur;
+            cur = (*node.as_ptr()).back;
+        }
+        self.front = head;
+        self.back = prev;
+    }
+}
+
+/// 排序时从链表上摘下的所有节点, 都是沿 `back` 方向的单链表;
+/// drop 时 (包括 `compare` panic 时) 把它们首尾相接, 重新挂回链表, 长度不变
+struct Detached<'a, T, A: Allocator> {
+    list: &'a mut LinkedList<T, A>,
+    // bins[i] 为空或是长度为 2^i 的有序单链表
+    bins: [Link<T>; usize::BITS as usize],
+    rest: Link<T>,
+    // 正常结束时是排好序的整个链表
+    carry: Link<T>,
+}
+
+impl<'a, T, A: Allocator> Detached<'a, T, A> {
+    fn new(list: &'a mut LinkedList<T, A>, rest: Link<T>) -> Self {
+        Self {
+            list,
+            bins: [None; usize::BITS as usize],
+            rest,
+            carry: None,
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> Drop for Detached<'a, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut all = self.rest.take();
+            for bin in self.bins.iter_mut().rev() {
+                all = concat(bin.take(), all);
+            }
+            all = concat(self.carry.take(), all);
+            self.list.relink_front(all);
+        }
+    }
+}
+
+/// 两个沿 `back` 方向的单链表首尾相接, 要走完 `left`
+unsafe fn concat<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    let mut tail = match left {
+        Some(node) => node,
+        None => return right,
+    };
+    while let Some(next) = (*tail.as_ptr()).back {
+        tail = next;
+    }
+    (*tail.as_ptr()).back = right;
+    left
+}
+
+/// 合并中的状态: 已合并的部分从 `*out` 开始, 到 `tail` 为止, 其余节点在 `left` `right` 中
+struct Merging<T> {
+    tail: *mut Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Drop for Merging<T> {
+    /// 正常结束时至少有一边已经取完; `compare` panic 时两边都有剩余, 依次接上, 一个节点也不丢
+    fn drop(&mut self) {
+        unsafe {
+            *self.tail = match (self.left, self.right) {
+                (Some(_), Some(_)) => concat(self.left, self.right),
+                (left, None) => left,
+                (None, right) => right,
+            };
+        }
+    }
+}
+
+/// 合并两个沿 `back` 方向的有序单链表, 结果写入 `*out`, 相等时取 `left` 的, 保证稳定
+unsafe fn merge<T, F>(left: Link<T>, right: Link<T>, out: &mut Link<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut state = Merging { tail: out, left, right };
+    while let (Some(l), Some(r)) = (state.left, state.right) {
+        let next = if compare(&(*l.as_ptr()).elem, &(*r.as_ptr()).elem) == Ordering::Greater {
+            state.right = (*r.as_ptr()).back;
+            r
+        } else {
+            state.left = (*l.as_ptr()).back;
+            l
+        };
+        *state.tail = Some(next);
+        state.tail = &mut (*next.as_ptr()).back;
+    }
+}
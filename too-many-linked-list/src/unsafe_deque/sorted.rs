@@ -0,0 +1,334 @@
+//! 始终保持有序的链表, 直接包装 `LinkedList`, 节点布局和 splice 归并等操作都复用它的
+//!
+//! 插入时记住新节点的位置, 下次插入从这里开始用游标向前或向后找,
+//! 所以按顺序插入一串元素时每次只走几步; 删除元素后这个位置可能失效, 随之清空
+use core::ops::{Bound, RangeBounds};
+use core::iter::Peekable;
+use super::*;
+
+/// 插入与已有元素相等的元素时怎么处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Duplicates {
+    /// 都保留, 新的排在相等元素的最后
+    Allow,
+    /// 保留已有的, 丢弃新的
+    Ignore,
+    /// 用新的替换已有的
+    Replace,
+}
+
+/// # Examples
+/// ```
+/// use too_many_linked_list::unsafe_deque::{Duplicates, SortedLinkedList};
+/// let mut set = SortedLinkedList::with_duplicates(Duplicates::Ignore);
+/// for x in [5, 1, 4, 1, 3] {
+///     set.insert(x);
+/// }
+/// assert_eq!(set.iter().collect::<Vec<_>>(), [&1, &3, &4, &5]);
+/// assert_eq!(set.range(2..5).collect::<Vec<_>>(), [&3, &4]);
+///
+/// let other: SortedLinkedList<_> = vec![3, 5, 6].into_iter().collect();
+/// assert_eq!(set.intersection(&other).collect::<Vec<_>>(), [&3, &5]);
+/// assert_eq!(set.difference(&other).collect::<Vec<_>>(), [&1, &4]);
+/// set.merge(other);
+/// assert_eq!(set.iter().collect::<Vec<_>>(), [&1, &3, &4, &5, &6]);
+/// ```
+pub struct SortedLinkedList<T, A: Allocator = Global> {
+    list: LinkedList<T, A>,
+    duplicates: Duplicates,
+    // 上次插入的节点及其下标
+    hint: Option<(NonNull<Node<T>>, usize)>,
+}
+
+impl<T: Ord> SortedLinkedList<T> {
+    /// 允许重复元素
+    pub const fn new() -> Self {
+        Self::with_duplicates(Duplicates::Allow)
+    }
+
+    pub const fn with_duplicates(duplicates: Duplicates) -> Self {
+        Self::new_in(duplicates, Global)
+    }
+}
+
+impl<T: Ord, A: Allocator> SortedLinkedList<T, A> {
+    pub const fn new_in(duplicates: Duplicates, alloc: A) -> Self {
+        Self {
+            list: LinkedList::new_in(alloc),
+            duplicates,
+            hint: None,
+        }
+    }
+
+    pub fn duplicates(&self) -> Duplicates {
+        self.duplicates
+    }
+
+    /// 插入到合适的位置, 返回没有留在链表中的元素:
+    /// `Ignore` 时是传入的 `elem`, `Replace` 时是被替换的旧元素, `Allow` 时总是 `None`
+    pub fn insert(&mut self, elem: T) -> Option<T> {
+        let mut cursor = match self.hint.take() {
+            // SAFETY: 删除元素时会清空 hint, 所以节点仍在链表中, 下标也没有变
+            Some((node, index)) => unsafe { CursorMut::at_node(&mut self.list, node, index) },
+            // 从幽灵元素开始就是从队尾往前找, 追加到末尾最快
+            None => self.list.cursor_mut(),
+        };
+        // 停在第一个大于 `elem` 的元素上, 没有则停在幽灵元素上
+        while cursor.current().is_some_and(|cur| *cur <= elem) {
+            cursor.move_next();
+        }
+        while cursor.peek_prev().is_some_and(|prev| *prev > elem) {
+            cursor.move_prev();
+        }
+        let dup = self.duplicates != Duplicates::Allow
+            && cursor.peek_prev().is_some_and(|prev| *prev == elem);
+        let rejected = if !dup {
+            cursor.insert_before(elem);
+            cursor.move_prev();
+            None
+        } else if self.duplicates == Duplicates::Ignore {
+            cursor.move_prev();
+            Some(elem)
+        } else {
+            cursor.move_prev();
+            cursor.replace_current(elem).ok()
+        };
+        // 游标停在新插入的 (或与之相等的) 元素上
+        self.hint = cursor.position();
+        rejected
+    }
+
+    /// 删除一个与 `elem` 相等的元素并返回
+    pub fn remove(&mut self, elem: &T) -> Option<T> {
+        self.hint = None;
+        let mut cursor = self.list.cursor_mut();
+        cursor.move_next();
+        while let Some(cur) = cursor.current() {
+            match (*cur).cmp(elem) {
+                Ordering::Less => cursor.move_next(),
+                Ordering::Equal => return cursor.remove_current(),
+                Ordering::Greater => break,
+            }
+        }
+        None
+    }
+
+    /// 走到第一个不小于 `elem` 的元素为止
+    pub fn contains(&self, elem: &T) -> bool {
+        self.list.iter().find(|x| *x >= elem) == Some(elem)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.hint = None;
+        self.list.pop_front()
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.hint = None;
+        self.list.pop_back()
+    }
+
+    /// 迭代落在 `range` 内的元素, 从队首开始找区间起点
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T> {
+        let before_start = |x: &T| match range.start_bound() {
+            Bound::Included(start) => x < start,
+            Bound::Excluded(start) => x <= start,
+            Bound::Unbounded => false,
+        };
+        let within_end = |x: &T| match range.end_bound() {
+            Bound::Included(end) => x <= end,
+            Bound::Excluded(end) => x < end,
+            Bound::Unbounded => true,
+        };
+        unsafe {
+            let mut front = self.list.front;
+            while let Some(node) = front.filter(|node| before_start(&(*node.as_ptr()).elem)) {
+                front = (*node.as_ptr()).back;
+            }
+            let (mut back, mut len) = (None, 0);
+            let mut next = front;
+            while let Some(node) = next.filter(|node| within_end(&(*node.as_ptr()).elem)) {
+                back = Some(node);
+                next = (*node.as_ptr()).back;
+                len += 1;
+            }
+            Iter::from_raw(front, back, len)
+        }
+    }
+
+    /// 归并 `other` 的所有节点, O(n + m), 再按本链表的策略处理相等的元素
+    pub fn merge(&mut self, other: Self) {
+        self.hint = None;
+        // 稳定归并, 相等时 `self` 的在前
+        self.list.merge_sorted(other.list);
+        if self.duplicates == Duplicates::Allow {
+            return;
+        }
+        let mut cursor = self.list.cursor_mut();
+        cursor.move_next();
+        while cursor.current().is_some() {
+            let dup = {
+                let cursor = cursor.as_cursor();
+                cursor.current() == cursor.peek_next()
+            };
+            if !dup {
+                cursor.move_next();
+            } else if self.duplicates == Duplicates::Ignore {
+                // 删掉后面的, 留下先到的
+                cursor.move_next();
+                cursor.remove_current();
+                cursor.move_prev();
+            } else {
+                // 删掉当前的, 游标移到后到的那个上
+                cursor.remove_current();
+            }
+        }
+    }
+
+    /// 同时在两个链表中的元素, 重复元素按较少的次数算, O(n + m)
+    pub fn intersection<'a, B: Allocator>(&'a self, other: &'a SortedLinkedList<T, B>) -> Intersection<'a, T> {
+        Intersection {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+        }
+    }
+
+    /// 在 `self` 中但不在 `other` 中的元素, 重复元素按次数相减, O(n + m)
+    pub fn difference<'a, B: Allocator>(&'a self, other: &'a SortedLinkedList<T, B>) -> Difference<'a, T> {
+        Difference {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+        }
+    }
+}
+
+impl<T, A: Allocator> SortedLinkedList<T, A> {
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.hint = None;
+        self.list.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// 只读地借出底层的链表, 可以使用游标 windows 等
+    pub fn as_list(&self) -> &LinkedList<T, A> {
+        &self.list
+    }
+
+    pub fn into_list(self) -> LinkedList<T, A> {
+        self.list
+    }
+}
+
+impl<T: Ord> Default for SortedLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Ord, A: Allocator> Extend<T> for SortedLinkedList<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for SortedLinkedList<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a SortedLinkedList<T, A> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// hint 只是指向自己链表中节点的指针
+unsafe impl<T: Send, A: Allocator + Send> Send for SortedLinkedList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for SortedLinkedList<T, A> {}
+
+/// 由 `SortedLinkedList::intersection` 创建
+pub struct Intersection<'a, T> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+}
+
+impl<'a, T: Ord> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (a, b) = (self.a.peek()?, self.b.peek()?);
+            match a.cmp(b) {
+                Ordering::Less => {
+                    self.a.next();
+                }
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            }
+        }
+    }
+}
+
+/// 由 `SortedLinkedList::difference` 创建
+pub struct Difference<'a, T> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+}
+
+impl<'a, T: Ord> Iterator for Difference<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = self.a.peek()?;
+            match self.b.peek().map(|b| a.cmp(b)) {
+                None | Some(Ordering::Less) => return self.a.next(),
+                Some(Ordering::Greater) => {
+                    self.b.next();
+                }
+                Some(Ordering::Equal) => {
+                    self.a.next();
+                    self.b.next();
+                }
+            }
+        }
+    }
+}
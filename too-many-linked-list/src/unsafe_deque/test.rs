@@ -130,7 +130,7 @@ fn test_rev_iter() {
         assert_eq!(6 - i as i32, *elt);
     }
     let mut n = LinkedList::new();
-    assert_eq!(n.iter().rev().next(), None);
+    assert_eq!(n.iter().next_back(), None);
     n.push_front(4);
     let mut it = n.iter().rev();
     assert_eq!(it.size_hint(), (1, Some(1)));
@@ -204,8 +204,9 @@ fn test_ord() {
 }
 
 #[test]
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
 fn test_ord_nan() {
-    let nan = 0.0f64 / 0.0;
+    let nan = f64::NAN;
     let n = list_from(&[nan]);
     let m = list_from(&[nan]);
     assert!(!(n < m));
@@ -240,7 +241,7 @@ fn test_debug() {
     let list: LinkedList<i32> = (0..10).collect();
     assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
 
-    let list: LinkedList<&str> = vec!["just", "one", "test", "more"]
+    let list: LinkedList<&str> = ["just", "one", "test", "more"]
         .iter()
         .copied()
         .collect();
@@ -334,7 +335,6 @@ fn test_cursor_mut_insert() {
         &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
     );
 
-    /* remove_current not impl'd
     let mut cursor = m.cursor_mut();
     cursor.move_next();
     cursor.move_prev();
@@ -350,7 +350,6 @@ fn test_cursor_mut_insert() {
     assert_eq!(cursor.remove_current(), Some(10));
     check_links(&m);
     assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
-    */
 
     let mut m: LinkedList<u32> = LinkedList::new();
     m.extend([1, 8, 2, 3, 4, 5, 6]);
@@ -371,7 +370,7 @@ fn test_cursor_mut_insert() {
     cursor.move_next();
     cursor.move_prev();
     let tmp = cursor.split_before();
-    assert_eq!(m.into_iter().collect::<Vec<_>>(), &[]);
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), Vec::<u32>::new());
     m = tmp;
     let mut cursor = m.cursor_mut();
     cursor.move_next();
@@ -393,10 +392,980 @@ fn test_cursor_mut_insert() {
     );
 }
 
-fn check_links<T: Eq + std::fmt::Debug>(list: &LinkedList<T>) {
+fn check_links<T: Eq + std::fmt::Debug, A: allocator_api2::alloc::Allocator>(list: &LinkedList<T, A>) {
     let from_front: Vec<_> = list.iter().collect();
     let from_back: Vec<_> = list.iter().rev().collect();
     let re_reved: Vec<_> = from_back.into_iter().rev().collect();
 
     assert_eq!(from_front, re_reved);
 }
+
+#[test]
+fn test_cursor_move_peek_readonly() {
+    let m: LinkedList<u32> = list_from(&[1, 2, 3, 4, 5, 6]);
+    let mut cursor = m.cursor();
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some(&1));
+    assert_eq!(cursor.peek_next(), Some(&2));
+    assert_eq!(cursor.peek_prev(), None);
+    assert_eq!(cursor.index(), Some(0));
+    cursor.move_prev();
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.peek_next(), Some(&1));
+    assert_eq!(cursor.peek_prev(), Some(&6));
+    assert_eq!(cursor.index(), None);
+
+    // 多个只读游标同时存在
+    let mut back = m.cursor();
+    back.move_prev();
+    back.move_prev();
+    assert_eq!(back.current(), Some(&5));
+    assert_eq!(back.index(), Some(4));
+    let cur = cursor.current();
+    let mut copied = back;
+    copied.move_next();
+    assert_eq!(copied.current(), Some(&6));
+    assert_eq!(back.current(), Some(&5));
+    assert_eq!(cur, None);
+
+    let empty: LinkedList<u32> = LinkedList::new();
+    let mut cursor = empty.cursor();
+    cursor.move_next();
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.index(), None);
+    cursor.move_prev();
+    assert_eq!(cursor.peek_next(), None);
+    assert_eq!(cursor.peek_prev(), None);
+}
+
+#[test]
+fn test_cursor_mut_as_cursor() {
+    let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+    let mut cursor = m.cursor_mut();
+    cursor.move_next();
+    cursor.move_next();
+    {
+        let view = cursor.as_cursor();
+        let mut other = view;
+        other.move_prev();
+        assert_eq!(view.current(), Some(&2));
+        assert_eq!(view.index(), Some(1));
+        assert_eq!(other.current(), Some(&1));
+    }
+    *cursor.current().unwrap() = 20;
+    assert_eq!(cursor.as_cursor().current(), Some(&20));
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 20, 3]);
+}
+
+#[test]
+fn test_cursor_mut_insert_remove_element() {
+    let mut m: LinkedList<u32> = LinkedList::new();
+    let mut cursor = m.cursor_mut();
+    // 空链表上, 游标在幽灵元素
+    cursor.insert_before(2); // 队尾
+    cursor.insert_after(1); // 队首
+    cursor.insert_before(3);
+    assert_eq!(cursor.index(), None);
+    assert_eq!(cursor.remove_current(), None);
+    assert_eq!(cursor.remove_current_as_list(), None);
+    assert_eq!(cursor.replace_current(9), Err(9));
+    check_links(&m);
+    assert_eq!(m.len(), 3);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+
+    let mut cursor = m.cursor_mut();
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some(&mut 2));
+    cursor.insert_before(10);
+    assert_eq!(cursor.index(), Some(2));
+    assert_eq!(cursor.current(), Some(&mut 2));
+    cursor.insert_after(20);
+    assert_eq!(cursor.index(), Some(2));
+    assert_eq!(cursor.peek_next(), Some(&mut 20));
+    assert_eq!(cursor.replace_current(200), Ok(2));
+    assert_eq!(cursor.current(), Some(&mut 200));
+    check_links(&m);
+    assert_eq!(m.len(), 5);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 10, 200, 20, 3]);
+
+    let mut cursor = m.cursor_mut();
+    cursor.move_next();
+    // 删除队首, 游标移到下一个, index 不变
+    assert_eq!(cursor.remove_current(), Some(1));
+    assert_eq!(cursor.current(), Some(&mut 10));
+    assert_eq!(cursor.index(), Some(0));
+    assert_eq!(cursor.peek_prev(), None);
+    cursor.move_prev();
+    cursor.move_prev();
+    // 删除队尾, 游标移到幽灵元素
+    assert_eq!(cursor.current(), Some(&mut 3));
+    let tail = cursor.remove_current_as_list().unwrap();
+    assert_eq!(tail.len(), 1);
+    assert_eq!(tail.iter().cloned().collect::<Vec<_>>(), &[3]);
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.index(), None);
+    assert_eq!(cursor.peek_prev(), Some(&mut 20));
+    check_links(&m);
+    assert_eq!(m.len(), 3);
+    assert_eq!(m.front(), Some(&10));
+    assert_eq!(m.back(), Some(&20));
+
+    let mut cursor = m.cursor_mut();
+    cursor.move_next();
+    while cursor.remove_current().is_some() {}
+    assert!(m.is_empty());
+    assert_eq!(m.front(), None);
+    assert_eq!(m.back(), None);
+    m.push_back(5);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5]);
+}
+
+#[test]
+fn test_append() {
+    let mut m = list_from(&[1, 2, 3]);
+    let mut n = list_from(&[4, 5]);
+    m.append(&mut n);
+    check_links(&m);
+    assert!(n.is_empty());
+    assert_eq!(m.len(), 5);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+
+    let mut e = LinkedList::new();
+    e.append(&mut m);
+    assert!(m.is_empty());
+    assert_eq!(e.len(), 5);
+    e.append(&mut m);
+    assert_eq!(e.len(), 5);
+    assert_eq!(e.back(), Some(&5));
+    check_links(&e);
+}
+
+#[test]
+fn test_split_off() {
+    for at in 0..=7 {
+        let mut m = generate_test();
+        let n = m.split_off(at);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(m.len(), at);
+        assert_eq!(n.len(), 7 - at);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), (0..at as i32).collect::<Vec<_>>());
+        assert_eq!(n.iter().cloned().collect::<Vec<_>>(), (at as i32..7).collect::<Vec<_>>());
+    }
+    let mut e: LinkedList<i32> = LinkedList::new();
+    assert!(e.split_off(0).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_split_off_out_of_bounds() {
+    let mut m = generate_test();
+    m.split_off(8);
+}
+
+#[test]
+fn test_retain() {
+    let mut m = generate_test();
+    m.retain(|x| x % 2 == 0);
+    check_links(&m);
+    assert_eq!(m.len(), 4);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 2, 4, 6]);
+
+    m.retain_mut(|x| {
+        *x += 1;
+        *x != 1 && *x != 7
+    });
+    check_links(&m);
+    assert_eq!(m.len(), 2);
+    assert_eq!(m.front(), Some(&3));
+    assert_eq!(m.back(), Some(&5));
+
+    m.retain(|_| false);
+    assert!(m.is_empty());
+    assert_eq!(m.front(), None);
+    assert_eq!(m.back(), None);
+}
+
+#[test]
+fn test_extract_if() {
+    let mut m = generate_test();
+    let mut visited = 0;
+    {
+        let mut it = m.extract_if(|x| {
+            visited += 1;
+            *x % 3 == 0
+        });
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next(), Some(3));
+    }
+    // 惰性, 6 还没被检查到
+    assert_eq!(visited, 4);
+    check_links(&m);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 4, 5, 6]);
+
+    let extracted: Vec<_> = m.extract_if(|x| *x > 3).collect();
+    assert_eq!(extracted, &[4, 5, 6]);
+    check_links(&m);
+    assert_eq!(m.len(), 2);
+    assert_eq!(m.back(), Some(&2));
+
+    let mut e: LinkedList<i32> = LinkedList::new();
+    assert_eq!(e.extract_if(|_| true).next(), None);
+}
+
+#[test]
+fn test_sort() {
+    let mut m: LinkedList<i32> = LinkedList::new();
+    m.sort();
+    assert!(m.is_empty());
+
+    let mut m = list_from(&[3]);
+    m.sort();
+    check_links(&m);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[3]);
+
+    // 简单的线性同余序列, 覆盖各种长度
+    let mut seed = 12345u32;
+    for len in 0..100 {
+        let v: Vec<u32> = (0..len).map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % 50
+        }).collect();
+        let mut m = list_from(&v);
+        let front = m.front().map(|x| x as *const u32);
+        m.sort();
+        check_links(&m);
+        let mut expected = v.clone();
+        expected.sort();
+        assert_eq!(m.len(), v.len());
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), expected);
+        assert_eq!(m.iter().rev().cloned().collect::<Vec<_>>(), expected.iter().rev().cloned().collect::<Vec<_>>());
+        // 只重排节点, 不移动元素
+        if let Some(front) = front {
+            assert!(m.iter().any(|x| std::ptr::eq(x, front)));
+        }
+    }
+}
+
+#[test]
+fn test_sort_stable() {
+    let mut m = list_from(&[(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd'), (1, 'e'), (2, 'f')]);
+    m.sort_by_key(|&(k, _)| k);
+    check_links(&m);
+    assert_eq!(
+        m.iter().cloned().collect::<Vec<_>>(),
+        &[(0, 'd'), (1, 'b'), (1, 'e'), (2, 'a'), (2, 'c'), (2, 'f')]
+    );
+
+    m.sort_by(|a, b| b.0.cmp(&a.0));
+    assert_eq!(
+        m.iter().map(|x| x.1).collect::<String>(),
+        "acfbed"
+    );
+    assert_eq!(m.front(), Some(&(2, 'a')));
+    assert_eq!(m.back(), Some(&(0, 'd')));
+}
+
+#[test]
+fn test_merge_sorted() {
+    let mut m = list_from(&[1, 3, 5, 7]);
+    m.merge_sorted(list_from(&[0, 2, 3, 8, 9]));
+    check_links(&m);
+    assert_eq!(m.len(), 9);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 3, 5, 7, 8, 9]);
+    assert_eq!(m.back(), Some(&9));
+
+    let mut e = LinkedList::new();
+    e.merge_sorted(list_from(&[1, 2]));
+    check_links(&e);
+    assert_eq!(e.len(), 2);
+    e.merge_sorted(LinkedList::new());
+    assert_eq!(e.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+
+    // 相等时 self 的在前
+    let mut a = list_from(&[(1, 'a'), (2, 'a')]);
+    a.merge_sorted_by(list_from(&[(1, 'b'), (2, 'b')]), |x, y| x.0.cmp(&y.0));
+    assert_eq!(a.iter().map(|x| x.1).collect::<String>(), "abab");
+}
+
+#[test]
+fn test_sort_panic_safety() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    // 比较若干次后 panic, 链表仍然完整, 节点一个不丢
+    let alloc = CountingAlloc::default();
+    for limit in [0, 1, 5, 20, 60] {
+        let mut m = LinkedList::new_in(alloc.clone());
+        m.extend((0..30).map(|x| x * 7 % 30));
+        let mut count = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.sort_by(|a, b| {
+                count += 1;
+                if count > limit {
+                    panic!("compare");
+                }
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert_eq!(m.len(), 30);
+        assert_eq!(m.get(29), m.back());
+        let mut elems: Vec<_> = m.iter().cloned().collect();
+        elems.sort();
+        assert_eq!(elems, (0..30).collect::<Vec<_>>());
+        // 之后照常使用
+        m.sort();
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), elems);
+
+        let mut other = LinkedList::new_in(alloc.clone());
+        other.extend(0..10);
+        let mut count = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.merge_sorted_by(other, |a, b| {
+                count += 1;
+                if count > limit {
+                    panic!("compare");
+                }
+                a.cmp(b)
+            })
+        }));
+        if limit < 10 {
+            assert!(result.is_err());
+        }
+        check_links(&m);
+        assert_eq!(m.len(), 40);
+        assert_eq!(m.get(39), m.back());
+        assert_eq!(m.iter().sum::<i32>(), (0..30).sum::<i32>() + (0..10).sum::<i32>());
+        assert_eq!(alloc.0.get(), 40);
+    }
+    assert_eq!(alloc.0.get(), 0);
+}
+
+/// 统计未释放的分配次数, 检查节点都用同一个分配器释放
+#[derive(Clone, Default)]
+struct CountingAlloc(std::rc::Rc<std::cell::Cell<isize>>);
+
+unsafe impl allocator_api2::alloc::Allocator for CountingAlloc {
+    fn allocate(&self, layout: allocator_api2::alloc::Layout) -> Result<std::ptr::NonNull<[u8]>, allocator_api2::alloc::AllocError> {
+        self.0.set(self.0.get() + 1);
+        allocator_api2::alloc::Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: std::ptr::NonNull<u8>, layout: allocator_api2::alloc::Layout) {
+        self.0.set(self.0.get() - 1);
+        allocator_api2::alloc::Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_custom_allocator() {
+    let alloc = CountingAlloc::default();
+    {
+        let mut m = LinkedList::new_in(alloc.clone());
+        m.extend([1, 2, 3, 4, 5, 6]);
+        m.push_front(0);
+        assert_eq!(alloc.0.get(), 7);
+        assert_eq!(m.pop_back(), Some(6));
+        assert_eq!(alloc.0.get(), 6);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.insert_after(10);
+        cursor.move_next();
+        let single = cursor.remove_current_as_list().unwrap();
+        assert_eq!(cursor.remove_current(), Some(1));
+        let tail = cursor.split_after();
+        assert_eq!(alloc.0.get(), 6);
+        assert_eq!(single.iter().cloned().collect::<Vec<_>>(), &[10]);
+        assert_eq!(tail.iter().cloned().collect::<Vec<_>>(), &[3, 4, 5]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 2]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.splice_before(tail);
+        cursor.splice_after(single);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[10, 0, 2, 3, 4, 5]);
+
+        let mut other = m.split_off(3);
+        m.append(&mut other);
+        m.sort();
+        let cloned = m.clone();
+        assert_eq!(alloc.0.get(), 12);
+        assert_eq!(cloned.into_iter().rev().collect::<Vec<_>>(), &[10, 5, 4, 3, 2, 0]);
+        assert_eq!(alloc.0.get(), 6);
+    }
+    assert_eq!(alloc.0.get(), 0);
+}
+
+/// 最简单的 bump 分配器, 只分配不释放, 整块内存随 arena 一起丢弃
+struct BumpArena {
+    buf: std::cell::UnsafeCell<[u64; 64]>,
+    used: std::cell::Cell<usize>,
+}
+
+unsafe impl allocator_api2::alloc::Allocator for &BumpArena {
+    fn allocate(&self, layout: allocator_api2::alloc::Layout) -> Result<std::ptr::NonNull<[u8]>, allocator_api2::alloc::AllocError> {
+        let base = self.buf.get() as *mut u8;
+        let start = (self.used.get() + layout.align() - 1) & !(layout.align() - 1);
+        let end = start + layout.size();
+        if layout.align() > 8 || end > 64 * 8 {
+            return Err(allocator_api2::alloc::AllocError);
+        }
+        self.used.set(end);
+        let ptr = unsafe { std::ptr::NonNull::new_unchecked(base.add(start)) };
+        Ok(std::ptr::NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: std::ptr::NonNull<u8>, _layout: allocator_api2::alloc::Layout) {}
+}
+
+#[test]
+fn test_bump_allocator() {
+    let arena = BumpArena {
+        buf: std::cell::UnsafeCell::new([0; 64]),
+        used: std::cell::Cell::new(0),
+    };
+    let mut m = LinkedList::new_in(&arena);
+    for i in 0..10u64 {
+        m.push_back(i);
+    }
+    // 每个节点两个指针加一个 u64
+    assert_eq!(arena.used.get(), 10 * 24);
+    m.retain(|x| x % 3 == 0);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 3, 6, 9]);
+    let tail = m.split_off(2);
+    assert_eq!(tail.into_iter().collect::<Vec<_>>(), &[6, 9]);
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn test_node_pool() {
+    let mut m: LinkedList<i32> = LinkedList::new();
+    assert_eq!(m.capacity(), 0);
+    m.push_back(1);
+    m.pop_back();
+    // 默认不回收
+    assert_eq!(m.spare_capacity(), 0);
+
+    m.reserve(3);
+    assert_eq!(m.spare_capacity(), 3);
+    assert_eq!(m.capacity(), 3);
+    // reserve 只预先分配, 不开启回收
+    assert!(!m.is_recycling());
+    m.push_back(0);
+    m.pop_back();
+    assert_eq!(m.spare_capacity(), 2);
+    m.reserve(0);
+    assert!(!m.is_recycling());
+
+    m.set_recycle(true);
+    m.extend([1, 2, 3, 4]);
+    assert_eq!(m.spare_capacity(), 0);
+    assert_eq!(m.capacity(), 4);
+
+    let front = m.front().unwrap() as *const i32;
+    assert_eq!(m.pop_front(), Some(1));
+    assert_eq!(m.spare_capacity(), 1);
+    // 复用刚刚 pop 出的节点
+    m.push_back(5);
+    assert!(std::ptr::eq(m.back().unwrap(), front));
+    assert_eq!(m.spare_capacity(), 0);
+    check_links(&m);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 3, 4, 5]);
+
+    m.clear();
+    assert_eq!(m.spare_capacity(), 4);
+    m.shrink_to_fit();
+    assert_eq!(m.capacity(), 0);
+    // 仍然在回收模式中, pop 出的节点继续留下
+    assert!(m.is_recycling());
+    m.push_back(1);
+    m.pop_back();
+    assert_eq!(m.spare_capacity(), 1);
+    // 显式关闭后才回到普通的分配方式, push 用掉空闲节点, pop 直接释放
+    m.set_recycle(false);
+    m.push_back(1);
+    m.pop_back();
+    assert_eq!(m.spare_capacity(), 0);
+
+    let mut m = LinkedList::with_capacity(2);
+    m.push_front(String::from("a"));
+    m.push_front(String::from("b"));
+    let mut cursor = m.cursor_mut();
+    cursor.move_next();
+    assert_eq!(cursor.remove_current().as_deref(), Some("b"));
+    cursor.insert_after(String::from("c"));
+    // 切出去的只有节点, 空闲节点和回收模式留在原链表
+    let tail = cursor.split_before();
+    assert_eq!(tail.spare_capacity(), 0);
+    assert_eq!(m.spare_capacity(), 0);
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &["a", "c"]);
+    m.pop_back();
+    assert_eq!(m.spare_capacity(), 1);
+}
+
+#[test]
+fn test_node_pool_allocator() {
+    let alloc = CountingAlloc::default();
+    {
+        let mut m = LinkedList::with_capacity_in(8, alloc.clone());
+        assert_eq!(alloc.0.get(), 8);
+        for round in 0..100 {
+            m.push_back(round);
+            m.push_front(round);
+            m.pop_back();
+            m.pop_front();
+        }
+        assert_eq!(alloc.0.get(), 8);
+        m.extend(0..10);
+        assert_eq!(alloc.0.get(), 10);
+        let mut n = m.split_off(0);
+        assert_eq!(n.len(), 10);
+        n.pop_back();
+        assert_eq!(alloc.0.get(), 9);
+        m.extend(0..5);
+        while m.len() > 2 {
+            m.pop_back();
+        }
+        assert_eq!(m.spare_capacity(), 3);
+        assert_eq!(alloc.0.get(), 9 + 5);
+        m.shrink_to_fit();
+        assert_eq!(alloc.0.get(), 9 + 2);
+    }
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn test_cursor_split_at_ends() {
+    let mut m = list_from(&[1, 2, 3]);
+    let mut cursor = m.cursor_mut();
+    cursor.move_next();
+    let before = cursor.split_before();
+    assert!(before.is_empty());
+    assert_eq!(before.front(), None);
+    assert_eq!(before.back(), None);
+    cursor.move_prev();
+    cursor.move_prev();
+    assert_eq!(cursor.current(), Some(&mut 3));
+    let after = cursor.split_after();
+    assert!(after.is_empty());
+    assert_eq!(after.front(), None);
+    assert_eq!(after.back(), None);
+    drop(before);
+    drop(after);
+    check_links(&m);
+    assert_eq!(m.len(), 3);
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_node_handle() {
+    let mut m = LinkedList::new();
+    let handles: Vec<_> = (0..6).map(|i| m.push_back_handle(i)).collect();
+    let front = m.push_front_handle(-1);
+    check_links(&m);
+    unsafe {
+        assert_eq!(*m.get_by_handle(front), -1);
+        assert_eq!(*m.get_by_handle(handles[3]), 3);
+        *m.get_mut_by_handle(handles[3]) = 30;
+
+        m.move_to_front(handles[3]);
+        m.move_to_front(handles[3]);
+        m.move_to_back(handles[0]);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), [30, -1, 1, 2, 4, 5, 0]);
+
+        assert_eq!(m.remove_by_handle(handles[4]), 4);
+        assert_eq!(m.remove_by_handle(handles[3]), 30);
+        assert_eq!(m.remove_by_handle(handles[0]), 0);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), [-1, 1, 2, 5]);
+
+        // 句柄在节点搬到别的链表后跟着节点走
+        let mut n = m.split_off(2);
+        assert_eq!(*n.get_by_handle(handles[5]), 5);
+        m.append(&mut n);
+        assert_eq!(*m.get_by_handle(handles[5]), 5);
+        n.push_back(9);
+        n.merge_sorted(m);
+        assert_eq!(n.remove_by_handle(handles[2]), 2);
+
+        let mut cursor = n.cursor_at_handle(handles[5]);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 5));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 9));
+        let mut cursor = n.cursor_at_handle(front);
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.remove_current(), Some(-1));
+        check_links(&n);
+        assert_eq!(n.iter().copied().collect::<Vec<_>>(), [1, 5, 9]);
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "stale")]
+fn test_node_handle_stale() {
+    let mut m = LinkedList::with_capacity(4);
+    let handle = m.push_back_handle(1);
+    m.pop_back();
+    // 回收模式下节点内存会被复用, 地址相同, 代数不同
+    let reused = m.push_back_handle(2);
+    assert_eq!(handle, reused);
+    unsafe { m.get_by_handle(handle) };
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "another list")]
+fn test_node_handle_foreign() {
+    let mut m = LinkedList::new();
+    let mut n = LinkedList::new();
+    m.push_back(1);
+    let handle = n.push_back_handle(2);
+    unsafe { m.remove_by_handle(handle) };
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use serde_test::{assert_tokens, Token};
+    let m: LinkedList<i32> = list_from(&[1, 2, 3]);
+    assert_tokens(&m, &[
+        Token::Seq { len: Some(3) },
+        Token::I32(1),
+        Token::I32(2),
+        Token::I32(3),
+        Token::SeqEnd,
+    ]);
+
+    let m: LinkedList<String> = serde_json::from_str(r#"["x", "y"]"#).unwrap();
+    check_links(&m);
+    assert_eq!(m.back().map(String::as_str), Some("y"));
+}
+
+#[test]
+fn test_check_invariants() {
+    let mut m = generate_test();
+    m.check_invariants();
+    let mut cursor = m.cursor_mut();
+    cursor.check_invariants();
+    cursor.move_prev();
+    cursor.move_prev();
+    cursor.check_invariants();
+    let tail = cursor.split_after();
+    tail.check_invariants();
+    m.check_invariants();
+    LinkedList::<i32>::with_capacity(3).check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "len does not match")]
+fn test_check_invariants_len() {
+    // 改坏的链表不能再 drop
+    let mut m = std::mem::ManuallyDrop::new(generate_test());
+    m.len += 1;
+    m.check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "do not mirror")]
+fn test_check_invariants_links() {
+    let m = std::mem::ManuallyDrop::new(generate_test());
+    unsafe {
+        let second = (*m.front.unwrap().as_ptr()).back.unwrap();
+        (*second.as_ptr()).front = None;
+    }
+    m.check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "cursor index")]
+fn test_check_invariants_cursor() {
+    let mut m = generate_test();
+    let front = m.front.unwrap();
+    let cursor = unsafe { super::CursorMut::at_node(&mut m, front, 2) };
+    cursor.check_invariants();
+}
+
+#[test]
+fn test_range() {
+    let mut m: LinkedList<i32> = (0..10).collect();
+    assert_eq!(m.range(..).count(), 10);
+    assert_eq!(m.range(3..3).next(), None);
+    assert_eq!(m.range(8..=9).copied().collect::<Vec<_>>(), [8, 9]);
+    let mut it = m.range(2..7);
+    assert_eq!(it.len(), 5);
+    assert_eq!(it.next(), Some(&2));
+    assert_eq!(it.next_back(), Some(&6));
+    assert_eq!(it.copied().collect::<Vec<_>>(), [3, 4, 5]);
+
+    for x in m.range_mut(1..4) {
+        *x *= 10;
+    }
+    assert_eq!(m.iter().take(5).copied().collect::<Vec<_>>(), [0, 10, 20, 30, 4]);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_range_out_of_bounds() {
+    let m: LinkedList<i32> = (0..3).collect();
+    m.range(1..4);
+}
+
+#[test]
+fn test_nth() {
+    let mut m: LinkedList<i32> = (0..10).collect();
+    let mut it = m.iter();
+    assert_eq!(it.nth(2), Some(&2));
+    assert_eq!(it.nth_back(2), Some(&7));
+    assert_eq!(it.len(), 4);
+    assert_eq!(it.nth(3), Some(&6));
+    assert_eq!(it.nth(1), None);
+
+    let mut it = m.iter_mut();
+    *it.nth(9).unwrap() = 90;
+    assert_eq!(it.nth_back(0), None);
+    assert_eq!(m.iter_mut().nth_back(10), None);
+    assert_eq!(m.back(), Some(&90));
+}
+
+#[test]
+fn test_windows_chunks() {
+    let m: LinkedList<i32> = (1..=5).collect();
+    let windows: Vec<Vec<i32>> = m.windows(2).map(|w| w.copied().collect()).collect();
+    assert_eq!(windows, [[1, 2], [2, 3], [3, 4], [4, 5]]);
+    assert_eq!(m.windows(5).len(), 1);
+    assert_eq!(m.windows(6).next().map(|w| w.len()), None);
+    // 窗口本身也是双端迭代器
+    assert_eq!(m.windows(3).last().unwrap().rev().copied().collect::<Vec<_>>(), [5, 4, 3]);
+
+    let mut chunks = m.chunks(3);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks.next().unwrap().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(chunks.next().unwrap().copied().collect::<Vec<_>>(), [4, 5]);
+    assert!(chunks.next().is_none());
+    assert_eq!(m.chunks(5).count(), 1);
+    assert_eq!(LinkedList::<i32>::new().chunks(2).count(), 0);
+    assert_eq!(LinkedList::<i32>::new().windows(1).count(), 0);
+}
+
+#[test]
+fn test_positional() {
+    let mut m: LinkedList<i32> = (0..6).collect();
+    assert_eq!(m.get(0), Some(&0));
+    assert_eq!(m.get(4), Some(&4));
+    assert_eq!(m.get(6), None);
+    *m.get_mut(5).unwrap() = 50;
+    m[1] += 10;
+    assert_eq!(m[1], 11);
+
+    m.insert(0, -1);
+    m.insert(7, 60);
+    m.insert(4, 100);
+    check_links(&m);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [-1, 0, 11, 2, 100, 3, 4, 50, 60]);
+    assert_eq!(m.remove(4), Some(100));
+    assert_eq!(m.remove(7), Some(60));
+    assert_eq!(m.remove(7), None);
+    assert_eq!(m.remove(0), Some(-1));
+    check_links(&m);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [0, 11, 2, 3, 4, 50]);
+
+    let mut cursor = m.cursor_at(4);
+    assert_eq!(cursor.current(), Some(&mut 4));
+    cursor.move_to(1);
+    assert_eq!((cursor.index(), cursor.current().copied()), (Some(1), Some(11)));
+    cursor.move_to(5);
+    assert_eq!((cursor.index(), cursor.current().copied()), (Some(5), Some(50)));
+    cursor.seek(1);
+    assert_eq!(cursor.index(), None);
+    cursor.seek(-8); // 7 个位置上循环, 相当于 -1
+    assert_eq!(cursor.index(), Some(5));
+    cursor.seek(10);
+    assert_eq!((cursor.index(), cursor.current().copied()), (Some(1), Some(11)));
+    cursor.move_to(6);
+    assert_eq!(cursor.current(), None);
+    assert_eq!(m.cursor_at(6).index(), None);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn test_index_out_of_bounds() {
+    let m: LinkedList<i32> = (0..3).collect();
+    let _ = m[3];
+}
+
+#[test]
+fn test_reverse_rotate_swap() {
+    let mut m = LinkedList::new();
+    let handles: Vec<_> = (0..6).map(|i| m.push_back_handle(i)).collect();
+    m.reverse();
+    check_links(&m);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [5, 4, 3, 2, 1, 0]);
+    m.reverse();
+
+    m.rotate_left(2);
+    check_links(&m);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [2, 3, 4, 5, 0, 1]);
+    m.rotate_right(5);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [3, 4, 5, 0, 1, 2]);
+    m.rotate_left(0);
+    m.rotate_right(6);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [3, 4, 5, 0, 1, 2]);
+
+    m.swap(0, 5);
+    check_links(&m);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [2, 4, 5, 0, 1, 3]);
+    m.swap(3, 2);
+    m.swap(1, 1);
+    check_links(&m);
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [2, 4, 0, 5, 1, 3]);
+
+    // 节点没有动过, 句柄仍然指向原来的元素
+    unsafe {
+        for (i, &handle) in handles.iter().enumerate() {
+            assert_eq!(*m.get_by_handle(handle), i);
+        }
+        m.move_to_front(handles[5]);
+    }
+    assert_eq!(m.front(), Some(&5));
+
+    let mut single: LinkedList<i32> = Some(1).into_iter().collect();
+    single.reverse();
+    single.rotate_left(1);
+    assert_eq!(single.front(), Some(&1));
+    LinkedList::<i32>::new().reverse();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_par() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut m: LinkedList<usize> = (0..10_000).collect();
+    let count = AtomicUsize::new(0);
+    let sum = AtomicUsize::new(0);
+    m.par_for_each(|x| {
+        count.fetch_add(1, Ordering::Relaxed);
+        sum.fetch_add(*x, Ordering::Relaxed);
+    });
+    assert_eq!(count.into_inner(), 10_000);
+    assert_eq!(sum.into_inner(), 10_000 * 9_999 / 2);
+
+    m.par_for_each_mut(|x| *x = *x * 3 + 1);
+    check_links(&m);
+    assert!(m.iter().copied().eq((0..10_000).map(|x| x * 3 + 1)));
+
+    let strings = m.par_map_into_vec(|x| x.to_string());
+    assert_eq!(strings.len(), 10_000);
+    assert!(strings.iter().zip(&m).all(|(s, x)| *s == x.to_string()));
+
+    let empty = LinkedList::<usize>::new();
+    empty.par_for_each(|_| unreachable!());
+    assert!(empty.par_map_into_vec(|x| *x).is_empty());
+    let one: LinkedList<usize> = Some(7).into_iter().collect();
+    assert_eq!(one.par_map_into_vec(|x| x + 1), [8]);
+}
+
+#[test]
+fn test_sorted() {
+    use super::{Duplicates, SortedLinkedList};
+
+    let mut m = SortedLinkedList::new();
+    for x in [5, 3, 8, 3, 1, 9, 7] {
+        assert_eq!(m.insert(x), None);
+    }
+    check_links(m.as_list());
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [1, 3, 3, 5, 7, 8, 9]);
+    assert!(m.contains(&7));
+    assert!(!m.contains(&4));
+    assert_eq!((m.first(), m.last()), (Some(&1), Some(&9)));
+    assert_eq!(m.range(3..=7).copied().collect::<Vec<_>>(), [3, 3, 5, 7]);
+    assert_eq!(m.range(4..).copied().collect::<Vec<_>>(), [5, 7, 8, 9]);
+    assert_eq!(m.range(..1).next(), None);
+    assert_eq!(m.range(10..).next(), None);
+
+    assert_eq!(m.remove(&3), Some(3));
+    assert_eq!(m.remove(&4), None);
+    assert_eq!(m.pop_first(), Some(1));
+    assert_eq!(m.pop_last(), Some(9));
+    // 删除后 hint 已清空, 继续插入仍然正确
+    m.insert(6);
+    m.insert(2);
+    check_links(m.as_list());
+    assert_eq!(m.iter().copied().collect::<Vec<_>>(), [2, 3, 5, 6, 7, 8]);
+
+    // 只按 0 比较, 用 1 区分相等的元素
+    #[derive(Debug)]
+    struct Key(i32, &'static str);
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for Key {}
+    impl PartialOrd for Key {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Key {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+    let tags = |m: &SortedLinkedList<Key>| m.iter().map(|k| k.1).collect::<String>();
+
+    // 相等时新的排在后面
+    let mut allow = SortedLinkedList::new();
+    for (k, tag) in [(2, "a"), (1, "b"), (2, "c"), (1, "d")] {
+        assert!(allow.insert(Key(k, tag)).is_none());
+    }
+    assert_eq!(tags(&allow), "bdac");
+
+    let mut ignore = SortedLinkedList::with_duplicates(Duplicates::Ignore);
+    ignore.insert(Key(1, "a"));
+    assert_eq!(ignore.insert(Key(1, "b")).map(|k| k.1), Some("b"));
+    let mut replace = SortedLinkedList::with_duplicates(Duplicates::Replace);
+    replace.insert(Key(1, "a"));
+    assert_eq!(replace.insert(Key(1, "b")).map(|k| k.1), Some("a"));
+    assert_eq!((tags(&ignore), tags(&replace)), ("a".to_string(), "b".to_string()));
+
+    // 归并后按各自的策略去重
+    let other = || IntoIterator::into_iter([(0, "x"), (1, "y"), (1, "z"), (3, "w")]).map(|(k, t)| Key(k, t)).collect();
+    ignore.merge(other());
+    replace.merge(other());
+    allow.merge(other());
+    assert_eq!(tags(&ignore), "xaw");
+    assert_eq!(tags(&replace), "xzw");
+    assert_eq!(tags(&allow), "xbdyzacw");
+    check_links(allow.as_list());
+}
+
+#[test]
+fn test_sorted_set_ops() {
+    use super::SortedLinkedList;
+
+    let a: SortedLinkedList<i32> = vec![1, 2, 2, 2, 4, 6, 8].into_iter().collect();
+    let b: SortedLinkedList<i32> = vec![2, 2, 3, 4, 5, 8, 9].into_iter().collect();
+    assert_eq!(a.intersection(&b).copied().collect::<Vec<_>>(), [2, 2, 4, 8]);
+    assert_eq!(a.difference(&b).copied().collect::<Vec<_>>(), [1, 2, 6]);
+    assert_eq!(b.difference(&a).copied().collect::<Vec<_>>(), [3, 5, 9]);
+    let empty = SortedLinkedList::new();
+    assert_eq!(a.intersection(&empty).count(), 0);
+    assert_eq!(a.difference(&empty).count(), a.len());
+
+    // 顺序插入走 hint, 乱序插入也要正确
+    let mut m = SortedLinkedList::new();
+    for x in (0..100).chain((0..100).rev()).chain((0..100).map(|x| x * 37 % 100)) {
+        m.insert(x);
+    }
+    let mut expected: Vec<_> = (0..100).flat_map(|x| [x, x, x]).collect();
+    expected.sort();
+    assert!(m.iter().copied().eq(expected));
+    check_links(m.as_list());
+}
@@ -1,4 +1,6 @@
 //! [7.2. Variance and PhantomData](https://rust-unofficial.github.io/too-many-lists/sixth-variance.html)
+// 演示型变的代码, 大多只求编译通过, 变量和赋值本身没有用处
+#![allow(unused, dropping_references, clippy::almost_swapped, clippy::drop_non_drop, clippy::unused_unit, clippy::mixed_attributes_style)]
 
 /// for type `F<T>`
 /// 1. 协变 F is covariant if F<Sub> is a subtype of F<Super> (subtyping "passes through")
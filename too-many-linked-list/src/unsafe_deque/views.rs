@@ -0,0 +1,191 @@
+//! 不分配内存的借用视图: 区间迭代, 滑动窗口与分块
+//! 每个窗口/块本身就是一个 `Iter`, 只记录首尾节点和长度
+use core::ops::{Bound, RangeBounds};
+use super::*;
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    /// 迭代 `range` 内的元素, 区间两端分别从较近的一端开始定位
+    /// # Panics
+    /// 区间起点大于终点或终点超过 `len` 时 panic
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let list: LinkedList<_> = (0..10).collect();
+    /// assert_eq!(list.range(2..5).copied().collect::<Vec<_>>(), [2, 3, 4]);
+    /// assert_eq!(list.range(7..).rev().copied().collect::<Vec<_>>(), [9, 8, 7]);
+    /// ```
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_, T> {
+        let (start, end, len) = self.range_nodes(range);
+        unsafe { Iter::from_raw(start, end, len) }
+    }
+
+    /// 同 `range`, 产出可变引用
+    pub fn range_mut<R: RangeBounds<usize>>(&mut self, range: R) -> IterMut<'_, T> {
+        let (start, end, len) = self.range_nodes(range);
+        unsafe { IterMut::from_raw(start, end, len) }
+    }
+
+    /// 长度为 `size` 的滑动窗口, 相邻窗口重叠 `size - 1` 个元素, 同 `slice::windows`
+    /// # Panics
+    /// `size == 0` 时 panic
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let list: LinkedList<_> = (1..=5).collect();
+    /// let sums: Vec<i32> = list.windows(3).map(|w| w.sum()).collect();
+    /// assert_eq!(sums, [6, 9, 12]);
+    /// ```
+    pub fn windows(&self, size: usize) -> Windows<'_, T> {
+        assert!(size != 0, "window size must be non-zero");
+        let remaining = (self.len + 1).saturating_sub(size);
+        let tail = if remaining > 0 { Some(self.node_at(size - 1)) } else { None };
+        Windows {
+            head: self.front,
+            tail,
+            size,
+            remaining,
+            _boo: PhantomData,
+        }
+    }
+
+    /// 不重叠的分块, 每块 `size` 个元素, 最后一块可能不足, 同 `slice::chunks`
+    /// # Panics
+    /// `size == 0` 时 panic
+    /// # Examples
+    /// ```
+    /// # use too_many_linked_list::unsafe_deque::LinkedList;
+    /// let list: LinkedList<_> = (1..=5).collect();
+    /// let chunks: Vec<Vec<i32>> = list.chunks(2).map(|c| c.copied().collect()).collect();
+    /// assert_eq!(chunks, [vec![1, 2], vec![3, 4], vec![5]]);
+    /// ```
+    pub fn chunks(&self, size: usize) -> Chunks<'_, T> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks {
+            front: self.front,
+            size,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    /// 区间首尾两个节点及区间长度, 空区间时首尾都为 `None`
+    fn range_nodes<R: RangeBounds<usize>>(&self, range: R) -> (Link<T>, Link<T>, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i.checked_add(1).expect("range start overflow"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i.checked_add(1).expect("range end overflow"),
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end, "range start {} is greater than end {}", start, end);
+        assert!(end <= self.len, "range end {} out of bounds for length {}", end, self.len);
+        if start == end {
+            return (None, None, 0);
+        }
+        (Some(self.node_at(start)), Some(self.node_at(end - 1)), end - start)
+    }
+}
+
+/// 由 `LinkedList::windows` 创建, 每个窗口是一个 `Iter`
+pub struct Windows<'a, T> {
+    // 下一个窗口的首尾节点
+    head: Link<T>,
+    tail: Link<T>,
+    size: usize,
+    // 还剩几个窗口
+    remaining: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iterator for Windows<'a, T> {
+    type Item = Iter<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let window = unsafe { Iter::from_raw(self.head, self.tail, self.size) };
+        self.remaining -= 1;
+        // 首尾各往后挪一个, 最后一个窗口之后不再挪, 免得 tail 走出链表
+        if self.remaining > 0 {
+            unsafe {
+                self.head = (*self.head.unwrap_unchecked().as_ptr()).back;
+                self.tail = (*self.tail.unwrap_unchecked().as_ptr()).back;
+            }
+        }
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Windows<'a, T> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, T> Clone for Windows<'a, T> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Windows<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Windows<'a, T> {}
+
+/// 由 `LinkedList::chunks` 创建, 每块是一个 `Iter`
+pub struct Chunks<'a, T> {
+    // 下一块的第一个节点
+    front: Link<T>,
+    size: usize,
+    // 还剩几个元素
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = Iter<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = self.size.min(self.len);
+        unsafe {
+            let mut back = self.front.unwrap_unchecked();
+            for _ in 1..len {
+                back = (*back.as_ptr()).back.unwrap_unchecked();
+            }
+            let chunk = Iter::from_raw(self.front, Some(back), len);
+            self.front = (*back.as_ptr()).back;
+            self.len -= len;
+            Some(chunk)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len();
+        (n, Some(n))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Chunks<'a, T> {
+    fn len(&self) -> usize {
+        self.len.div_ceil(self.size)
+    }
+}
+
+impl<'a, T> Clone for Chunks<'a, T> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Chunks<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Chunks<'a, T> {}
@@ -16,7 +16,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
 }
 
 impl<'a, T> Iter<'a, T> {
-    pub fn new(list: &List<T>) -> Iter<T> {
+    pub fn new(list: &List<T>) -> Iter<'_, T> {
         Iter(&list.head)
     }
     pub fn peek(&self) -> Option<&T> {
@@ -25,13 +25,13 @@ impl<'a, T> Iter<'a, T> {
 }
 
 impl<T> List<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter::new(self)
     }
 }
 
-impl<T: std::fmt::Debug> std::fmt::Debug for List<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: core::fmt::Debug> core::fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_list().entries(self.iter()).finish()
     }
 }
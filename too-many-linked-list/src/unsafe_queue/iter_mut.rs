@@ -49,7 +49,7 @@ impl<'a, T> IterMut<'a, T> {
 }
 
 impl<T> List<T> {
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut::new(self)
     }
 }
\ No newline at end of file
@@ -2,12 +2,15 @@
 
 use core::ptr::NonNull;
 use core::mem::replace;
+use alloc::boxed::Box;
 
 mod iter;
 mod iter_mut;
 mod into_iter;
 #[cfg(test)]
 mod test;
+#[cfg(feature = "serde")]
+mod serde;
 
 /// 如下函数编译成功证明了 `List<T>` 对 `T` 协变
 /// ```no_run
@@ -105,7 +108,7 @@ impl<T> HeapOwnerExt<T> for NonNull<T> {
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         // Pop until we have to stop
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
     }
 }
 
@@ -115,14 +118,16 @@ impl<T> List<T> {
     }
     /// pop from head
     pub fn pop_front(&mut self) -> Option<T> {
-        self.head.take().map(|old_head| {
+        let elem = self.head.take().map(|old_head| {
             let old_head = old_head.into_value();
             if old_head.next.is_none() {
                 self.tail = None;
             }
             self.head = old_head.next;
             old_head.elem
-        })
+        });
+        self.debug_check();
+        elem
     }
     pub fn push_front(&mut self, elem: T) {
         let new_node = HeapOwner::create(Node { elem, next: self.head.take() });
@@ -131,6 +136,7 @@ impl<T> List<T> {
         if self.tail.is_none() {
             self.tail = new_node_ptr;
         }
+        self.debug_check();
     }
     /// push to tail
     pub fn push_back(&mut self, elem: T) {
@@ -146,6 +152,7 @@ impl<T> List<T> {
             }
         }
         self.tail = new_tail;
+        self.debug_check();
     }
 
     pub fn peek(&self) -> Option<&T> {
@@ -161,6 +168,36 @@ impl<T> List<T> {
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 不变量检查
+impl<T> List<T> {
+    /// `head` `tail` 同时为空, 否则 `tail` 指向从 `head` 走到的最后一个节点, 不满足时 panic, O(n)
+    pub fn check_invariants(&self) {
+        match (self.head.as_ref(), self.tail) {
+            (None, None) => {}
+            (Some(mut node), Some(tail)) => {
+                while let Some(next) = &node.as_ref_value().next {
+                    node = next;
+                }
+                assert_eq!(tail.as_ptr() as *const _, node.as_ref_value() as *const _, "tail is not the last node");
+            }
+            _ => panic!("only one of head and tail is set"),
+        }
+    }
+
+    /// `debug-invariants` 开启时检查不变量, 否则什么都不做
+    #[inline(always)]
+    fn debug_check(&self) {
+        #[cfg(feature = "debug-invariants")]
+        self.check_invariants();
+    }
+}
+
 /// 原地反转
 pub fn reverse<T>(ls: &mut List<T>) {
     let newtail = ls.head.as_ref()
@@ -177,4 +214,5 @@ pub fn reverse<T>(ls: &mut List<T>) {
     }
     ls.head = newhead;
     ls.tail = newtail;
+    ls.debug_check();
 }
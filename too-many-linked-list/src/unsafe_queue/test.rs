@@ -60,9 +60,9 @@ fn peek() {
     assert_eq!(list.peek(), Some(&1));
     assert_eq!(list.peek_mut(), Some(&mut 1));
 
-    list.peek_mut().map(|value| {
+    if let Some(value) = list.peek_mut() {
         *value = 42
-    });
+    }
     valid(&list);
 
     assert_eq!(list.peek(), Some(&42));
@@ -137,3 +137,38 @@ fn test_reverse() {
     valid(&list);
     assert_eq!(list.iter().collect::<Vec<_>>(), [&3, &2, &1]);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use serde_test::{assert_ser_tokens, Token};
+    let mut list = List::new();
+    list.push_back(1);
+    list.push_back(2);
+    assert_ser_tokens(&list, &[Token::Seq { len: None }, Token::I32(1), Token::I32(2), Token::SeqEnd]);
+
+    let list: List<i32> = serde_json::from_str("[4, 5, 6]").unwrap();
+    valid(&list);
+    assert_eq!(list.iter().collect::<Vec<_>>(), [&4, &5, &6]);
+}
+
+#[test]
+fn check_invariants() {
+    let mut list = List::new();
+    list.check_invariants();
+    list.push_back(1);
+    list.push_front(0);
+    list.check_invariants();
+    reverse(&mut list);
+    list.check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "tail is not the last node")]
+fn check_invariants_tail() {
+    let mut list = std::mem::ManuallyDrop::new(List::new());
+    list.push_back(1);
+    list.push_back(2);
+    list.tail = list.head.as_ref().and_then(HeapOwnerExt::as_weak_ptr);
+    list.check_invariants();
+}